@@ -0,0 +1,122 @@
+//! Benchmarks of source indexing: the memory taken by the index of segments
+//! and line breaks, and the time taken by lookups through it.
+//!
+//! Run with `cargo bench --bench indexing`.
+
+use nom_grapheme_clusters::{Location, Source};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    mem::size_of,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::Instant,
+};
+
+/// Allocator counting the bytes currently allocated.
+struct Counting;
+
+/// Bytes currently allocated.
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Number of lookups timed per benchmark.
+const LOOKUPS: usize = 1 << 20;
+
+/// Creates contents of roughly the given size in bytes, mixing ASCII, combining
+/// marks and line breaks.
+fn contents(size: usize) -> String {
+    let line = "fn main() { let café = \"n\u{329}\u{30a}\"; }\n";
+    line.repeat(size / line.len() + 1)
+}
+
+/// Pseudo-random positions below the given bound.
+fn positions(bound: usize) -> Vec<usize> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    (0 .. LOOKUPS)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % bound as u64) as usize
+        })
+        .collect()
+}
+
+/// Times the given lookup, called with every index below [`LOOKUPS`],
+/// printing the time per lookup.
+fn time<F>(name: &str, mut lookup: F)
+where
+    F: FnMut(usize) -> usize,
+{
+    let start = Instant::now();
+    let mut sum = 0usize;
+    for index in 0 .. LOOKUPS {
+        sum = sum.wrapping_add(lookup(index));
+    }
+    black_box(sum);
+    let elapsed = start.elapsed();
+    println!(
+        "{:>24}: {:>8.2} ns/lookup",
+        name,
+        elapsed.as_nanos() as f64 / LOOKUPS as f64
+    );
+}
+
+fn main() {
+    for size in [1 << 10, 1 << 16, 1 << 20, 1 << 24] {
+        let contents = contents(size);
+        let contents_len = contents.len();
+        let before = ALLOCATED.load(Ordering::Relaxed);
+        let source = Source::new("bench.rs", contents);
+        // The contents are moved into the source, already allocated.
+        let index_bytes = ALLOCATED.load(Ordering::Relaxed) - before;
+        let naive_bytes =
+            (source.len() + 1 + source.line_count()) * size_of::<usize>();
+
+        println!(
+            "{} bytes, {} segments, {} lines",
+            contents_len,
+            source.len(),
+            source.line_count()
+        );
+        println!(
+            "{:>24}: {} bytes ({:.2} bytes/segment, {:.1}% of usize arrays)",
+            "index memory",
+            index_bytes,
+            index_bytes as f64 / source.len() as f64,
+            index_bytes as f64 * 100.0 / naive_bytes as f64
+        );
+        assert!(index_bytes < naive_bytes);
+
+        let positions = positions(source.len());
+        time("Source::get", |index| {
+            source.get(positions[index]).map_or(0, str::len)
+        });
+        let locations: Vec<_> = positions
+            .iter()
+            .map(|&position| Location::new(source.clone(), position))
+            .collect();
+        time("Location::line", |index| locations[index].line());
+        time("Source::location_at_byte", |index| {
+            let byte = positions[index] * contents_len / source.len();
+            source
+                .location_at_byte(byte)
+                .map_or(0, |location| location.position())
+        });
+        println!();
+    }
+}
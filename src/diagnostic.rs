@@ -0,0 +1,403 @@
+//! This module provides rendering of source code snippets with labelled spans
+//! underlined, as in compiler diagnostics.
+//!
+//! # Examples
+//! ```
+//! use nom_grapheme_clusters::{
+//!     diagnostic::{Label, Snippet},
+//!     Source,
+//! };
+//!
+//! let source = Source::new("main.txt", "let 名前 = 1;\n");
+//! let name = source.full_span().try_slice(4 .. 6).unwrap();
+//! let mut snippet = Snippet::new();
+//! snippet.label(Label::primary(name, "unknown name"));
+//! let expected = " --> main.txt:1:5
+//!   |
+//! 1 | let 名前 = 1;
+//!   |     ^^^^ unknown name
+//! ";
+//! assert_eq!(snippet.to_string(), expected);
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{location::advance_visual_column, Source, Span};
+use std::{collections::BTreeSet, fmt};
+
+/// ANSI escape code of the gutter and the header.
+const GUTTER_COLOR: &str = "\x1b[1;34m";
+/// ANSI escape code of primary labels.
+const PRIMARY_COLOR: &str = "\x1b[1;31m";
+/// ANSI escape code of secondary labels.
+const SECONDARY_COLOR: &str = "\x1b[1;36m";
+/// ANSI escape code resetting colors.
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// Style of a label, telling whether it points to the cause of a diagnostic
+/// or to related code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LabelStyle {
+    /// The cause of the diagnostic, underlined with `^`.
+    Primary,
+    /// Code related to the diagnostic, underlined with `-`.
+    Secondary,
+}
+
+impl LabelStyle {
+    /// The character used to underline spans of this style.
+    fn marker(self) -> char {
+        match self {
+            Self::Primary => '^',
+            Self::Secondary => '-',
+        }
+    }
+
+    /// The ANSI escape code of this style.
+    fn color(self) -> &'static str {
+        match self {
+            Self::Primary => PRIMARY_COLOR,
+            Self::Secondary => SECONDARY_COLOR,
+        }
+    }
+}
+
+/// A span with a message, to be underlined in a [`Snippet`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Label {
+    /// The span underlined.
+    span: Span,
+    /// Whether this is a primary or secondary label.
+    style: LabelStyle,
+    /// Message written after the underline. May be empty.
+    message: Box<str>,
+}
+
+impl Label {
+    /// Creates a label given its span, style and message. The message may be
+    /// empty.
+    pub fn new<S>(span: Span, style: LabelStyle, message: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self { span, style, message: message.into() }
+    }
+
+    /// Creates a primary label (see [`LabelStyle::Primary`]).
+    pub fn primary<S>(span: Span, message: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self::new(span, LabelStyle::Primary, message)
+    }
+
+    /// Creates a secondary label (see [`LabelStyle::Secondary`]).
+    pub fn secondary<S>(span: Span, message: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self::new(span, LabelStyle::Secondary, message)
+    }
+
+    /// The span underlined by this label.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// The style of this label.
+    pub fn style(&self) -> LabelStyle {
+        self.style
+    }
+
+    /// The message of this label, possibly empty.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// The last line this label underlines. A non-empty span ending right
+    /// after a line break does not underline the next line.
+    fn last_line(&self) -> usize {
+        let end = self.span.end();
+        if !self.span.is_empty() && end.column() == 0 {
+            end.line() - 1
+        } else {
+            end.line()
+        }
+    }
+}
+
+/// Renderer of the source lines around labelled spans, with a gutter of line
+/// numbers and the spans underlined. Underlines are measured in display
+/// width, so they line up below combining marks, wide characters and emoji.
+///
+/// Rendered through [`fmt::Display`]. Labels of different sources are
+/// rendered in separate sections, in the order the sources first appear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    /// Labels to be rendered.
+    labels: Vec<Label>,
+    /// Lines shown before and after labelled lines.
+    context_lines: usize,
+    /// Width of tab stops.
+    tab_width: usize,
+    /// Whether ANSI colors are written.
+    colors: bool,
+}
+
+impl Default for Snippet {
+    fn default() -> Self {
+        Self {
+            labels: Vec::new(),
+            context_lines: 0,
+            tab_width: 4,
+            colors: false,
+        }
+    }
+}
+
+impl Snippet {
+    /// Creates a new snippet with no labels and default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a label to the snippet.
+    pub fn label(&mut self, label: Label) -> &mut Self {
+        self.labels.push(label);
+        self
+    }
+
+    /// Sets how many lines are shown before and after the labelled lines.
+    /// Defaults to `0`.
+    pub fn context_lines(&mut self, lines: usize) -> &mut Self {
+        self.context_lines = lines;
+        self
+    }
+
+    /// Sets the width of tab stops, tabs being expanded to spaces. Defaults to
+    /// `4`.
+    pub fn tab_width(&mut self, width: usize) -> &mut Self {
+        self.tab_width = width;
+        self
+    }
+
+    /// Sets whether the gutter and the underlines are colored with ANSI
+    /// escape codes. Defaults to `false`.
+    pub fn colors(&mut self, enabled: bool) -> &mut Self {
+        self.colors = enabled;
+        self
+    }
+
+    /// The labels of this snippet, in the order they were added.
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+
+    /// Groups labels by source, in the order sources first appear.
+    fn groups(&self) -> Vec<(&Source, Vec<&Label>)> {
+        let mut groups: Vec<(&Source, Vec<&Label>)> = Vec::new();
+        for label in &self.labels {
+            let source = label.span.source();
+            match groups.iter_mut().find(|(other, _)| *other == source) {
+                Some((_, labels)) => labels.push(label),
+                None => groups.push((source, vec![label])),
+            }
+        }
+        groups
+    }
+
+    /// Lines shown for the given labels of a single source.
+    fn shown_lines(
+        &self,
+        source: &Source,
+        labels: &[&Label],
+    ) -> BTreeSet<usize> {
+        let last = source.line_count() - 1;
+        let mut lines = BTreeSet::new();
+        for label in labels {
+            let start =
+                label.span.start().line().saturating_sub(self.context_lines);
+            let end =
+                label.last_line().saturating_add(self.context_lines).min(last);
+            lines.extend(start ..= end);
+        }
+        lines
+    }
+
+    /// Writes text with the given color, if colors are enabled.
+    fn paint(
+        &self,
+        fmtr: &mut fmt::Formatter,
+        color: &str,
+        text: fmt::Arguments,
+    ) -> fmt::Result {
+        if self.colors {
+            write!(fmtr, "{}{}{}", color, text, RESET_COLOR)
+        } else {
+            fmtr.write_fmt(text)
+        }
+    }
+
+    /// Writes the gutter, with the line number if given.
+    fn gutter(
+        &self,
+        fmtr: &mut fmt::Formatter,
+        width: usize,
+        line: Option<usize>,
+    ) -> fmt::Result {
+        match line {
+            Some(line) => self.paint(
+                fmtr,
+                GUTTER_COLOR,
+                format_args!("{:>width$} |", line + 1, width = width),
+            ),
+            None => self.paint(
+                fmtr,
+                GUTTER_COLOR,
+                format_args!("{:width$} |", "", width = width),
+            ),
+        }
+    }
+
+    /// Writes a line of the source, with tabs expanded.
+    fn source_line(
+        &self,
+        fmtr: &mut fmt::Formatter,
+        content: &Span,
+    ) -> fmt::Result {
+        let mut column = 0;
+        for segment in content.segments() {
+            let next = advance_visual_column(column, &segment, self.tab_width);
+            if segment == *"\t" {
+                write!(fmtr, "{:1$}", "", next - column)?;
+            } else {
+                fmtr.write_str(&segment)?;
+            }
+            column = next;
+        }
+        Ok(())
+    }
+
+    /// Writes the underline of a label on the given line, if the label
+    /// touches it.
+    fn underline(
+        &self,
+        fmtr: &mut fmt::Formatter,
+        gutter_width: usize,
+        line: usize,
+        label: &Label,
+    ) -> fmt::Result {
+        let span = &label.span;
+        let source = span.source();
+        if line < span.start().line() || line > label.last_line() {
+            return Ok(());
+        }
+        let line_span = match source.line(line) {
+            Some(line_span) => line_span,
+            None => unreachable!(
+                "Line {} is shown but not found in {}",
+                line,
+                source.name()
+            ),
+        };
+        let line_start = line_span.start().position();
+        let start = span.start().position().max(line_start);
+        let end = span.end().position().min(line_span.end().position());
+
+        let mut column = 0;
+        let mut start_column = 0;
+        for position in line_start .. end {
+            if position == start {
+                start_column = column;
+            }
+            let segment = &source[position];
+            column = if source.line_break_policy().is_line_break(segment) {
+                column + 1
+            } else {
+                advance_visual_column(column, segment, self.tab_width)
+            };
+        }
+        if start == end {
+            start_column = column;
+        }
+        let width = (column - start_column).max(1);
+
+        self.gutter(fmtr, gutter_width, None)?;
+        write!(fmtr, " {:1$}", "", start_column)?;
+        let marker = label.style.marker();
+        let markers: String = (0 .. width).map(|_| marker).collect();
+        self.paint(fmtr, label.style.color(), format_args!("{}", markers))?;
+        if line == label.last_line() && !label.message.is_empty() {
+            fmtr.write_str(" ")?;
+            self.paint(
+                fmtr,
+                label.style.color(),
+                format_args!("{}", label.message),
+            )?;
+        }
+        fmtr.write_str("\n")
+    }
+}
+
+impl fmt::Display for Snippet {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let groups = self.groups();
+        let shown: Vec<_> = groups
+            .iter()
+            .map(|(source, labels)| self.shown_lines(source, labels))
+            .collect();
+        let max_line = shown
+            .iter()
+            .filter_map(|lines| lines.last())
+            .max()
+            .map_or(0, |line| line + 1);
+        let gutter_width = max_line.to_string().len();
+
+        for ((source, labels), lines) in groups.iter().zip(&shown) {
+            let main = labels
+                .iter()
+                .find(|label| label.style == LabelStyle::Primary)
+                .unwrap_or(&labels[0]);
+            let (line, column) = main.span.start().line_column();
+            self.paint(
+                fmtr,
+                GUTTER_COLOR,
+                format_args!("{:1$}-->", "", gutter_width),
+            )?;
+            writeln!(fmtr, " {}:{}:{}", source.name(), line + 1, column + 1)?;
+            self.gutter(fmtr, gutter_width, None)?;
+            fmtr.write_str("\n")?;
+
+            let mut labels = labels.clone();
+            labels.sort_by_key(|label| label.span.start().position());
+            let mut previous = None;
+            for &line in lines {
+                if previous.is_some_and(|previous| previous + 1 < line) {
+                    self.paint(fmtr, GUTTER_COLOR, format_args!("..."))?;
+                    fmtr.write_str("\n")?;
+                }
+                previous = Some(line);
+
+                let content = match source.line_content(line) {
+                    Some(content) => content,
+                    None => unreachable!(
+                        "Line {} is shown but not found in {}",
+                        line,
+                        source.name()
+                    ),
+                };
+                self.gutter(fmtr, gutter_width, Some(line))?;
+                if !content.is_empty() {
+                    fmtr.write_str(" ")?;
+                    self.source_line(fmtr, &content)?;
+                }
+                fmtr.write_str("\n")?;
+                for label in &labels {
+                    self.underline(fmtr, gutter_width, line, label)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,51 @@
+use super::{Label, Snippet};
+use crate::Source;
+
+#[test]
+fn wide_and_combining_segments() {
+    let source = Source::new("test.txt", "a\u{301}b 🦀\tx\n");
+    let span = source.full_span();
+    let mut snippet = Snippet::new();
+    snippet
+        .label(Label::secondary(span.try_slice(0 .. 2).unwrap(), "name"))
+        .label(Label::primary(span.try_slice(3 .. 4).unwrap(), "crab"))
+        .label(Label::primary(span.try_slice(5 .. 6).unwrap(), ""));
+    assert_eq!(
+        snippet.to_string(),
+        concat!(
+            " --> test.txt:1:4\n", "  |\n", "1 | a\u{301}b 🦀   x\n",
+            "  | -- name\n", "  |    ^^ crab\n", "  |         ^\n",
+        ),
+    );
+}
+
+#[test]
+fn multiple_lines_and_context() {
+    let source =
+        Source::new("test.txt", "one\ntwo\nthree\nfour\nfive\nsix\nseven\n");
+    let span = source.full_span();
+    let mut snippet = Snippet::new();
+    snippet
+        .context_lines(1)
+        .label(Label::primary(span.try_slice(4 .. 11).unwrap(), "block"))
+        .label(Label::secondary(span.try_slice(28 .. 33).unwrap(), "end"));
+    assert_eq!(
+        snippet.to_string(),
+        concat!(
+            " --> test.txt:2:1\n", "  |\n", "1 | one\n", "2 | two\n",
+            "  | ^^^^\n", "3 | three\n", "  | ^^^ block\n", "4 | four\n",
+            "...\n", "6 | six\n", "7 | seven\n", "  | ----- end\n", "8 |\n",
+        ),
+    );
+}
+
+#[test]
+fn ansi_colors() {
+    let source = Source::new("test.txt", "x");
+    let mut snippet = Snippet::new();
+    snippet.colors(true).label(Label::primary(source.full_span(), "here"));
+    let rendered = snippet.to_string();
+    assert!(rendered.contains("\x1b[1;31m^\x1b[0m"));
+    assert!(rendered.contains("\x1b[1;31mhere\x1b[0m"));
+    assert!(rendered.contains("\x1b[1;34m1 |\x1b[0m x"));
+}
@@ -1,7 +1,8 @@
 //! This crate provides location for `nom` with support to grapheme clusters.
 //!
 //! # Examples
-//! ```
+#![cfg_attr(feature = "parse", doc = "```")]
+#![cfg_attr(not(feature = "parse"), doc = "```ignore")]
 //! use nom::{combinator::map, IResult};
 //! use nom_grapheme_clusters::{parse::Tag, Source, Span, SpanContent};
 //!
@@ -58,9 +59,74 @@
 pub mod source;
 mod location;
 pub mod span;
+#[cfg(feature = "serde")]
+pub mod serialization;
+pub mod diagnostic;
+pub mod stream;
+#[cfg(any(feature = "parse", feature = "nom8"))]
+mod tag;
+#[cfg(any(feature = "parse", feature = "nom8"))]
+mod parsers;
 #[cfg(feature = "parse")]
 pub mod parse;
+#[cfg(feature = "nom8")]
+pub mod parse8;
 
-pub use location::{LocatedSegment, Location};
-pub use source::Source;
+pub use location::{ColumnUnit, LocatedSegment, Location};
+pub use source::{Source, SourceMap};
 pub use span::{Span, SpanContent};
+pub use stream::{StreamingSource, StreamingSpan};
+
+/// Builds a struct whose fields are tag [`Span`]s, segmenting each tag once.
+/// All tags of a table share a single hidden [`Source`], named after the
+/// struct, so that the spans can be used as tags, e.g. with
+/// `nom::bytes::complete::tag`, instead of hand-segmented
+/// [`Tag`](parse::Tag)s.
+///
+/// # Examples
+#[cfg_attr(feature = "parse", doc = "```")]
+#[cfg_attr(not(feature = "parse"), doc = "```ignore")]
+/// use nom::{bytes::complete::tag, IResult};
+/// use nom_grapheme_clusters::{tag_table, Source, Span};
+///
+/// struct Keywords {
+///     atn: Span,
+///     smth: Span,
+/// }
+///
+/// let keywords = tag_table! {
+///     Keywords {
+///         atn: "atn̩̊",
+///         smth: "smth",
+///     }
+/// };
+/// assert_eq!(keywords.atn.len(), 3);
+///
+/// let source = Source::new("file.txt", "atn̩̊smth");
+/// let result: IResult<Span, Span> = tag(&keywords.atn)(source.full_span());
+/// let (rest, parsed) = result.unwrap();
+/// assert_eq!(parsed.as_str(), "atn̩̊");
+/// assert_eq!(rest.as_str(), "smth");
+/// ```
+#[macro_export]
+macro_rules! tag_table {
+    ($name:path { $($field:ident : $tag:expr),* $(,)? }) => {{
+        $(let $field: &str = $tag;)*
+        // Tags are separated by a control character, which always breaks
+        // grapheme clusters, so that no tag merges with the next one.
+        let contents = <[&str]>::join(&[$($field),*], "\0");
+        let source = $crate::Source::new(::core::stringify!($name), contents);
+        let mut position = 0;
+        let mut next_span = |tag: &str| {
+            let length =
+                $crate::source::Segmenter::count(source.segmenter(), tag);
+            let span = $crate::Span::new(
+                $crate::Location::new(source.clone(), position),
+                length,
+            );
+            position += length + 1;
+            span
+        };
+        $name { $($field: next_span($field)),* }
+    }};
+}
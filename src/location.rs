@@ -1,6 +1,9 @@
 //! This module provides means of tracking location in a source code.
 
-use crate::{source::Source, span::Span};
+use crate::{
+    source::{FileId, FileLocation, Source},
+    span::Span,
+};
 use std::{
     borrow::Borrow,
     cmp::Ordering,
@@ -8,6 +11,45 @@ use std::{
     hash::{Hash, Hasher},
     ops::Deref,
 };
+use unicode_width::UnicodeWidthStr;
+
+/// Unit in which a column is measured. Different consumers count columns
+/// differently: editors speaking the Language Server Protocol use UTF-16 code
+/// units, terminals use display width, and many tools use bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum ColumnUnit {
+    /// Grapheme clusters/segments.
+    #[default]
+    Segments,
+    /// Unicode scalar values (Rust `char`s).
+    Chars,
+    /// UTF-16 code units.
+    Utf16,
+    /// UTF-8 bytes.
+    Bytes,
+    /// Display width in a monospaced terminal, where East Asian wide
+    /// characters and most emoji take two columns and combining marks take
+    /// none.
+    DisplayWidth,
+}
+
+/// Advances a visual column past the given segment, expanding tabs to the
+/// next multiple of `tab_width` and measuring other segments by display width.
+/// A zero `tab_width` makes tabs take no columns.
+pub(crate) fn advance_visual_column(
+    column: usize,
+    segment: &str,
+    tab_width: usize,
+) -> usize {
+    if segment == "\t" {
+        match tab_width {
+            0 => column,
+            _ => (column / tab_width + 1) * tab_width,
+        }
+    } else {
+        column + segment.width()
+    }
+}
 
 /// The location in a source code.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -30,7 +72,7 @@ impl Location {
     /// # Panics
     /// Panics if `position` is past beyond source length in number of segments.
     pub fn new(source: Source, position: usize) -> Self {
-        if source.len() < position {
+        if source.try_byte_index(position).is_none() {
             panic!(
                 "Location position is too big; availabe: {}, given: {}",
                 source.len(),
@@ -51,24 +93,100 @@ impl Location {
         &mut self.position
     }
 
+    /// This location's offset in the source code contents in terms of bytes.
+    pub fn byte_offset(&self) -> usize {
+        self.source.byte_index(self.position)
+    }
+
+    /// This location's offset in the original contents of the source, before
+    /// normalization, in terms of bytes (see [`Source::new_normalized`]). The
+    /// same as [`Location::byte_offset`] if the source was not normalized.
+    pub fn original_byte_offset(&self) -> usize {
+        self.source.original_byte_index(self.byte_offset(), false)
+    }
+
+    /// This location in the original contents of the source, before
+    /// normalization (see [`Source::original_source`]). A location inside
+    /// what an original segment was normalized into maps to the start of that
+    /// segment. The same as this location if the source was not normalized.
+    pub fn original_location(&self) -> Location {
+        let original = self.source.original_source();
+        let position = match original.search_byte(self.original_byte_offset()) {
+            Ok(position) => position,
+            Err(position) => position - 1,
+        };
+        Location::new_unchecked(original.clone(), position)
+    }
+
+    /// This location's position in the original contents of the source,
+    /// before normalization, in terms of segments (see
+    /// [`Location::original_location`]).
+    pub fn original_position(&self) -> usize {
+        self.original_location().position()
+    }
+
+    /// Finds the line and column of this location in the original contents
+    /// of the source, before normalization (see
+    /// [`Location::original_location`]).
+    pub fn original_line_column(&self) -> (usize, usize) {
+        self.original_location().line_column()
+    }
+
     /// The source code object this location refers to.
     pub fn source(&self) -> &Source {
         &self.source
     }
 
+    /// The ID of the source code object in its [`SourceMap`], if any.
+    ///
+    /// [`SourceMap`]: crate::source::SourceMap
+    pub fn file_id(&self) -> Option<FileId> {
+        self.source.file_id()
+    }
+
+    /// This location in the compact form of a [`SourceMap`], carrying the
+    /// file ID rather than the source. Returns `None` if the source is not
+    /// registered in a map.
+    ///
+    /// [`SourceMap`]: crate::source::SourceMap
+    pub fn file_location(&self) -> Option<FileLocation> {
+        Some(FileLocation::new(self.file_id()?, self.position))
+    }
+
     /// Finds the line and column (respectively) of this location in the source
     /// code. Line and column count grapheme clusters/segments, not bytes nor
     /// characters.
     pub fn line_column(&self) -> (usize, usize) {
-        let line = self.source.line(self.position);
+        let line = self.source.line_of(self.position);
         let line_start = self.source.line_start(line);
         (line, self.position - line_start)
     }
 
+    /// Finds the line and column (respectively) of this location in the source
+    /// code, where the column is measured in the given unit. Lines are always
+    /// counted in terms of line breaks.
+    pub fn line_column_in(&self, unit: ColumnUnit) -> (usize, usize) {
+        let line = self.source.line_of(self.position);
+        let line_start = self.source.line_start(line);
+        let line_text = || {
+            let start = self.source.byte_index(line_start);
+            let end = self.source.byte_index(self.position);
+            &self.source.contents()[start .. end]
+        };
+        let column = match unit {
+            ColumnUnit::Segments => self.position - line_start,
+            ColumnUnit::Chars => line_text().chars().count(),
+            ColumnUnit::Utf16 => line_text().encode_utf16().count(),
+            ColumnUnit::Bytes => line_text().len(),
+            ColumnUnit::DisplayWidth => line_text().width(),
+        };
+        (line, column)
+    }
+
     /// Finds the line of this location in the source code. Line counts grapheme
     /// clusters/segments, not bytes nor characters.
     pub fn line(&self) -> usize {
-        self.source.line(self.position)
+        self.source.line_of(self.position)
     }
 
     /// Finds the column of this location in the source code. Column counts
@@ -78,6 +196,28 @@ impl Location {
         column
     }
 
+    /// Finds the column of this location in the source code, measured in the
+    /// given unit.
+    pub fn column_in(&self, unit: ColumnUnit) -> usize {
+        let (_, column) = self.line_column_in(unit);
+        column
+    }
+
+    /// Finds the column of this location as a user sees it, with tabs expanded
+    /// to the next multiple of `tab_width` (tab stops) and other segments
+    /// measured by their display width (see [`ColumnUnit::DisplayWidth`]).
+    /// A zero `tab_width` makes tabs take no columns.
+    pub fn visual_column(&self, tab_width: usize) -> usize {
+        let line_start = self.source.line_start(self.line());
+        let start = Self::new_unchecked(self.source.clone(), line_start);
+        Span::from_range(start, self.clone()).segments().fold(
+            0,
+            |column, segment| {
+                advance_visual_column(column, &segment, tab_width)
+            },
+        )
+    }
+
     /// Returns the underlying grapheme cluster segment content at this
     /// location.
     pub fn as_str(&self) -> &str {
@@ -89,15 +229,13 @@ impl Location {
         LocatedSegment { location: self.clone() }
     }
 
-    /// Creates a [`Span`] containing the whole line this location is in.
+    /// Creates a [`Span`] containing the whole line this location is in,
+    /// including its line break.
     pub fn line_span(&self) -> Span {
-        let line = self.line();
-        let init = self.source().line_start(line);
-        let end = self
-            .source()
-            .try_line_start(line + 1)
-            .unwrap_or(self.source().len());
-        Span::new(Self::new(self.source.clone(), init), end - init)
+        match self.source.line(self.line()) {
+            Some(span) => span,
+            None => unreachable!("Location's line must exist"),
+        }
     }
 }
 
@@ -158,40 +296,37 @@ impl LocatedSegment {
     /// Tests whether this segment is alphabetic. UTF-8 alphabetic characters
     /// with diacritics are also considered alphabetic.
     pub fn is_alphabetic(&self) -> bool {
-        self.chars().next().map_or(false, |ch| ch.is_alphabetic())
+        self.chars().next().is_some_and(|ch| ch.is_alphabetic())
     }
 
     /// Tests whether this segment is ASCII alphabetic.
     pub fn is_ascii_alphabetic(&self) -> bool {
         self.is_single_char()
-            && self.chars().next().map_or(false, |ch| ch.is_ascii_alphabetic())
+            && self.chars().next().is_some_and(|ch| ch.is_ascii_alphabetic())
     }
 
     /// Tests whether this segment is numeric. UTF-8 numeric characters
     /// with diacritics are also considered numeric.
     pub fn is_numeric(&self) -> bool {
-        self.chars().next().map_or(false, |ch| ch.is_numeric())
+        self.chars().next().is_some_and(|ch| ch.is_numeric())
     }
 
     /// Tests whether this segment is ASCII numeric.
     pub fn is_ascii_numeric(&self) -> bool {
         self.is_single_char()
-            && self.chars().next().map_or(false, |ch| ch.is_ascii_digit())
+            && self.chars().next().is_some_and(|ch| ch.is_ascii_digit())
     }
 
     /// Tests whether this segment is alphanumeric. UTF-8 alphanumeric
     /// characters with diacritics are also considered alphanumeric.
     pub fn is_alphanumeric(&self) -> bool {
-        self.chars().next().map_or(false, |ch| ch.is_alphanumeric())
+        self.chars().next().is_some_and(|ch| ch.is_alphanumeric())
     }
 
     /// Tests whether this segment is ASCII alphanumeric.
     pub fn is_ascii_alphanumeric(&self) -> bool {
         self.is_single_char()
-            && self
-                .chars()
-                .next()
-                .map_or(false, |ch| ch.is_ascii_alphanumeric())
+            && self.chars().next().is_some_and(|ch| ch.is_ascii_alphanumeric())
     }
 
     /// Tests whether this segment is an ASCII digit. Digits characters with
@@ -199,7 +334,7 @@ impl LocatedSegment {
     /// `a-z`, `A-Z`, depending on the base.
     pub fn is_digit(&self, base: u32) -> bool {
         self.is_single_char()
-            && self.chars().next().map_or(false, |ch| ch.is_digit(base))
+            && self.chars().next().is_some_and(|ch| ch.is_digit(base))
     }
 
     /// Converts this grapheme cluster to a digit of given base. Digits with
@@ -217,6 +352,18 @@ impl LocatedSegment {
         self == "\n"
     }
 
+    /// Tests if this segment is a line break according to the
+    /// [`LineBreakPolicy`] of its source.
+    ///
+    /// [`LineBreakPolicy`]: crate::source::LineBreakPolicy
+    pub fn is_line_break(&self) -> bool {
+        let source = self.location.source();
+        let following = &source.contents()[self.location.byte_offset() ..];
+        source
+            .line_break_policy()
+            .ends_line(self, &following[self.as_str().len() ..])
+    }
+
     /// Tests whether this segment is a single space.
     pub fn is_space(&self) -> bool {
         self == " "
@@ -0,0 +1,146 @@
+//! Exports parse functions related to segments/grapheme clusters, built on
+//! nom 8. The same parsers, built on nom 7, are found in
+//! [`parse`](crate::parse) with the `parse` feature.
+
+#[cfg(test)]
+mod test {
+    crate::parsers::segment_tests!(nom8);
+}
+mod error {
+    crate::parsers::define_errors!(nom8);
+}
+mod marks {
+    crate::parsers::define_marks!(nom8, InputLength: Input, InputTake: Input);
+}
+mod parsers {
+    // nom 8 merged the input traits of nom 7 into `Input`.
+    crate::parsers::define_segment_parsers!(
+        nom8,
+        InputIter: Input,
+        InputLength: Input,
+        InputTake: Input,
+        InputTakeAtPosition: Input,
+    );
+}
+
+pub use crate::tag::{ExpectTag, OwnedTag, Tag};
+use crate::{
+    span::{Spanned, Symbol},
+    LocatedSegment,
+    Span,
+};
+pub use error::{ExpectError, Expected, GraphemeError};
+pub use marks::{tag_no_marks, CompareNoMarks};
+use nom8::{
+    error::{ErrorKind, ParseError},
+    ExtendInto,
+    IResult,
+    Input,
+    Offset,
+    Parser,
+};
+pub use parsers::*;
+
+/// Executes the parser returning any data automatically combing the span of
+/// such data into a symbol.
+pub fn symbol<T, E, P, A>(
+    mut parser: P,
+) -> impl FnMut(T) -> IResult<T, Symbol<A>, E>
+where
+    T: Spanned,
+    E: ParseError<T>,
+    P: Parser<T, Output = A, Error = E>,
+{
+    move |input| {
+        let start = input.span().start();
+        let (new_input, data) = parser.parse(input)?;
+        let end = new_input.span().end();
+        let span = Span::from_range(start, end);
+        Ok((new_input, Symbol { span, data }))
+    }
+}
+
+/// Runs the given parser, replacing a recoverable error by one expecting the
+/// given item at the parser's input. Failures are kept as they are.
+pub fn expecting<T, O, E, P>(
+    expected: Expected,
+    mut parser: P,
+) -> impl FnMut(T) -> IResult<T, O, E>
+where
+    T: Clone,
+    E: ExpectError<T>,
+    P: Parser<T, Output = O, Error = E>,
+{
+    move |input: T| match parser.parse(input.clone()) {
+        Err(nom8::Err::Error(_)) => {
+            Err(nom8::Err::Error(E::from_expected(input, expected.clone())))
+        },
+        result => result,
+    }
+}
+
+/// Matches input with escaped segments, transforming it into an output such
+/// as a [`String`], like nom's `escaped_transform`. `normal` parses text
+/// without escapes. Where it stops, a segment made only of `control_char`
+/// starts an escape, which `transform` parses. A segment merely starting with
+/// `control_char`, such as `"\\\u{301}"`, does not.
+///
+/// nom 8's own `escaped_transform` requires input items to convert to
+/// `char`s, which [`LocatedSegment`]s of many characters cannot do without
+/// loss, so it cannot parse spans. This one always works as nom's complete
+/// version.
+pub fn escaped_transform<T, E, F, G, O1, O2, X, O>(
+    mut normal: F,
+    control_char: char,
+    mut transform: G,
+) -> impl FnMut(T) -> IResult<T, O, E>
+where
+    T: Clone + Offset + Input<Item = LocatedSegment>,
+    T: ExtendInto<Item = X, Extender = O>,
+    O1: ExtendInto<Item = X, Extender = O>,
+    O2: ExtendInto<Item = X, Extender = O>,
+    E: ParseError<T>,
+    F: Parser<T, Output = O1, Error = E>,
+    G: Parser<T, Output = O2, Error = E>,
+{
+    move |input: T| {
+        let mut output = input.new_builder();
+        let mut index = 0;
+        while index < input.input_len() {
+            let remainder = input.take_from(index);
+            let rest = match normal.parse(remainder.clone()) {
+                Ok((rest, parsed)) => {
+                    if rest.input_len() == remainder.input_len() {
+                        return Ok((remainder, output));
+                    }
+                    parsed.extend_into(&mut output);
+                    rest
+                },
+                Err(nom8::Err::Error(_)) => {
+                    let escapes = remainder.iter_elements().next().is_some_and(
+                        |segment| {
+                            segment.as_str()
+                                == control_char.encode_utf8(&mut [0; 4])
+                        },
+                    );
+                    if !escapes && index > 0 {
+                        return Ok((remainder, output));
+                    }
+                    if !escapes || index + 1 >= input.input_len() {
+                        return Err(nom8::Err::Error(E::from_error_kind(
+                            remainder,
+                            ErrorKind::EscapedTransform,
+                        )));
+                    }
+                    let (rest, parsed) =
+                        transform.parse(input.take_from(index + 1))?;
+                    parsed.extend_into(&mut output);
+                    rest
+                },
+                Err(error) => return Err(error),
+            };
+            index = input.offset(&rest);
+        }
+        Ok((input.take_from(index), output))
+    }
+}
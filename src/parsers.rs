@@ -0,0 +1,20 @@
+//! Macros defining the parsers, error type and comparisons shared by `parse`
+//! and `parse8`, given the nom crate each of them is built on.
+
+mod error;
+mod marks;
+mod segments;
+
+pub(crate) use error::define_errors;
+#[cfg(test)]
+pub(crate) use error::error_tests;
+pub(crate) use marks::define_marks;
+#[cfg(test)]
+pub(crate) use marks::marks_tests;
+#[cfg(test)]
+pub(crate) use segments::segment_tests;
+pub(crate) use segments::{
+    define_segment_parsers,
+    parse_signed_int,
+    parse_unsigned_int,
+};
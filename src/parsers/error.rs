@@ -0,0 +1,286 @@
+//! Error type for parsers over spans, defined for each nom crate.
+
+#[cfg(test)]
+mod test;
+
+#[cfg(test)]
+pub(crate) use test::error_tests;
+
+/// Defines an error type for parsers over spans, which keeps the location of
+/// the failure, what was expected there, and context labels, given the nom
+/// crate.
+macro_rules! define_errors {
+    ($nom:ident) => {
+        #[cfg(test)]
+        mod test {
+            crate::parsers::error_tests!($nom);
+        }
+
+        use crate::{
+            span::Spanned,
+            tag::{OwnedTag, Tag},
+            Location,
+        };
+        use std::{error::Error, fmt, sync::Arc};
+        use $nom::error::{
+            ContextError,
+            ErrorKind,
+            FromExternalError,
+            ParseError,
+        };
+
+        /// Something a parser expected to find.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum Expected {
+            /// A literal sequence of segments, such as a keyword.
+            Tag(Box<str>),
+            /// A single character.
+            Char(char),
+            /// A class of segments or an input condition, described by the
+            /// kind of error nom reported (e.g. [`ErrorKind::Alpha`] for
+            /// alphabetic segments).
+            Kind(ErrorKind),
+            /// A custom description.
+            Label(Box<str>),
+        }
+
+        impl Expected {
+            /// The nom error kind closest to this expected item, used by errors
+            /// that only record kinds.
+            pub fn error_kind(&self) -> ErrorKind {
+                match self {
+                    Self::Tag(_) | Self::Label(_) => ErrorKind::Tag,
+                    Self::Char(_) => ErrorKind::Char,
+                    Self::Kind(kind) => *kind,
+                }
+            }
+        }
+
+        impl<'slice, 'seg> From<Tag<'slice, 'seg>> for Expected {
+            fn from(tag: Tag<'slice, 'seg>) -> Self {
+                Self::Tag(tag.segments().collect::<String>().into())
+            }
+        }
+
+        impl<'tag> From<&'tag OwnedTag> for Expected {
+            fn from(tag: &'tag OwnedTag) -> Self {
+                Self::Tag(tag.as_str().into())
+            }
+        }
+
+        impl fmt::Display for Expected {
+            fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+                match self {
+                    Self::Tag(text) => write!(fmtr, "{:?}", text),
+                    Self::Char(ch) => write!(fmtr, "{:?}", ch),
+                    Self::Label(label) => write!(fmtr, "{}", label),
+                    Self::Kind(kind) => fmtr.write_str(match kind {
+                        ErrorKind::Alpha => "alphabetic segment",
+                        ErrorKind::Digit => "digit",
+                        ErrorKind::HexDigit => "hexadecimal digit",
+                        ErrorKind::OctDigit => "octal digit",
+                        ErrorKind::AlphaNumeric => "alphanumeric segment",
+                        ErrorKind::Space | ErrorKind::MultiSpace => {
+                            "whitespace"
+                        },
+                        ErrorKind::CrLf => "line ending",
+                        ErrorKind::Eof => "any segment",
+                        _ => return write!(fmtr, "{}", kind.description()),
+                    }),
+                }
+            }
+        }
+
+        /// Parse errors that can record what was expected, in addition to nom's
+        /// error kinds.
+        pub trait ExpectError<I>: ParseError<I> {
+            /// Creates an error from the input where something else was
+            /// expected.
+            fn from_expected(input: I, expected: Expected) -> Self;
+        }
+
+        impl<I> ExpectError<I> for $nom::error::Error<I> {
+            fn from_expected(input: I, expected: Expected) -> Self {
+                Self::from_error_kind(input, expected.error_kind())
+            }
+        }
+
+        /// A parse error aware of locations in grapheme clusters/segments. It
+        /// records the location of the failure, the set of items expected
+        /// there, and the context labels (see [`ContextError`]) of the
+        /// enclosing parsers.
+        ///
+        /// Alternatives (see [`ParseError::or`]) keep the error that got
+        /// furthest into the input, merging the expected items of errors at the
+        /// same location.
+        #[derive(Debug, Clone)]
+        pub struct GraphemeError {
+            /// Where the parser failed.
+            location: Location,
+            /// Items expected at the location, without duplicates.
+            expected: Vec<Expected>,
+            /// Context labels, innermost first, with the location where each
+            /// context started.
+            contexts: Vec<(Location, &'static str)>,
+            /// Error from outside of parsing, such as converting a parsed
+            /// number.
+            external: Option<Arc<dyn Error + Send + Sync>>,
+        }
+
+        impl GraphemeError {
+            /// Creates an error at the given location, expecting the given
+            /// item.
+            pub fn new(location: Location, expected: Expected) -> Self {
+                Self {
+                    location,
+                    expected: vec![expected],
+                    contexts: Vec::new(),
+                    external: None,
+                }
+            }
+
+            /// The location where the parser failed.
+            pub fn location(&self) -> &Location {
+                &self.location
+            }
+
+            /// The items expected at the location.
+            pub fn expected(&self) -> &[Expected] {
+                &self.expected
+            }
+
+            /// The context labels of the enclosing parsers, innermost first,
+            /// with the location where each context started.
+            pub fn contexts(&self) -> &[(Location, &'static str)] {
+                &self.contexts
+            }
+
+            /// The error from outside of parsing that caused this error, if
+            /// any.
+            pub fn external(
+                &self,
+            ) -> Option<&(dyn Error + Send + Sync + 'static)> {
+                self.external.as_deref()
+            }
+
+            /// Adds an item to the expected set, unless already there.
+            fn expect(&mut self, expected: Expected) {
+                if !self.expected.contains(&expected) {
+                    self.expected.push(expected);
+                }
+            }
+        }
+
+        impl<I> ParseError<I> for GraphemeError
+        where
+            I: Spanned,
+        {
+            fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+                Self::new(input.span().start(), Expected::Kind(kind))
+            }
+
+            fn append(_input: I, _kind: ErrorKind, other: Self) -> Self {
+                other
+            }
+
+            fn from_char(input: I, ch: char) -> Self {
+                Self::new(input.span().start(), Expected::Char(ch))
+            }
+
+            fn or(self, other: Self) -> Self {
+                let (mut furthest, closest) = if self.location.source()
+                    != other.location.source()
+                    || self.location.position() < other.location.position()
+                {
+                    (other, self)
+                } else {
+                    (self, other)
+                };
+                if furthest.location == closest.location {
+                    for expected in closest.expected {
+                        furthest.expect(expected);
+                    }
+                    if furthest.external.is_none() {
+                        furthest.external = closest.external;
+                    }
+                }
+                furthest
+            }
+        }
+
+        impl<I> ContextError<I> for GraphemeError
+        where
+            I: Spanned,
+        {
+            fn add_context(
+                input: I,
+                ctx: &'static str,
+                mut other: Self,
+            ) -> Self {
+                other.contexts.push((input.span().start(), ctx));
+                other
+            }
+        }
+
+        impl<I, E> FromExternalError<I, E> for GraphemeError
+        where
+            I: Spanned,
+            E: Error + Send + Sync + 'static,
+        {
+            fn from_external_error(
+                input: I,
+                kind: ErrorKind,
+                error: E,
+            ) -> Self {
+                let mut this = Self::from_error_kind(input, kind);
+                this.external = Some(Arc::new(error));
+                this
+            }
+        }
+
+        impl<I> ExpectError<I> for GraphemeError
+        where
+            I: Spanned,
+        {
+            fn from_expected(input: I, expected: Expected) -> Self {
+                Self::new(input.span().start(), expected)
+            }
+        }
+
+        impl fmt::Display for GraphemeError {
+            fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+                fmtr.write_str("expected ")?;
+                let count = self.expected.len();
+                for (index, expected) in self.expected.iter().enumerate() {
+                    if index > 0 {
+                        fmtr.write_str(if index + 1 == count {
+                            " or "
+                        } else {
+                            ", "
+                        })?;
+                    }
+                    write!(fmtr, "{}", expected)?;
+                }
+                write!(fmtr, " {}", self.location)?;
+                if let Some(external) = &self.external {
+                    write!(fmtr, ": {}", external)?;
+                }
+                for (location, context) in &self.contexts {
+                    write!(fmtr, "\n  while parsing {} {}", context, location)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl Error for GraphemeError {
+            fn source(&self) -> Option<&(dyn Error + 'static)> {
+                match &self.external {
+                    Some(external) => Some(&**external),
+                    None => None,
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use define_errors;
@@ -0,0 +1,80 @@
+/// Defines the tests of the error type of a nom crate, given the crate.
+macro_rules! error_tests {
+    ($nom:ident) => {
+        use super::{
+            super::{alpha1, ascii_numeric1, expecting, Tag},
+            Expected,
+            GraphemeError,
+        };
+        use crate::{Source, Span};
+        use $nom::{
+            branch::alt,
+            combinator::map_res,
+            error::{context, ErrorKind},
+            sequence::preceded,
+            IResult,
+            Parser,
+        };
+
+        fn keyword(input: Span) -> IResult<Span, Span, GraphemeError> {
+            alt((Tag(&["l", "e", "t"]).expect(), Tag(&["f", "n"]).expect()))
+                .parse(input)
+        }
+
+        #[test]
+        fn merges_expected_set() {
+            let source = Source::new("test.txt", "var x");
+            let error = match keyword(source.full_span()) {
+                Err($nom::Err::Error(error)) => error,
+                other => panic!("expected error, got {:?}", other),
+            };
+            assert_eq!(error.location().position(), 0);
+            assert_eq!(error.expected(), [
+                Expected::Tag("let".into()),
+                Expected::Tag("fn".into())
+            ]);
+            assert_eq!(
+                error.to_string(),
+                "expected \"let\" or \"fn\" in test.txt (1, 1)"
+            );
+        }
+
+        #[test]
+        fn keeps_furthest_alternative() {
+            let source = Source::new("test.txt", "let 1");
+            let mut parser = context(
+                "declaration",
+                alt((
+                    preceded(Tag(&["l", "e", "t", " "]).expect(), alpha1),
+                    expecting(Expected::Label("number".into()), ascii_numeric1),
+                )),
+            );
+            let error: GraphemeError = match parser.parse(source.full_span()) {
+                Err($nom::Err::Error(error)) => error,
+                other => panic!("expected error, got {:?}", other),
+            };
+            assert_eq!(error.location().position(), 4);
+            assert_eq!(error.expected(), [Expected::Kind(ErrorKind::Alpha)]);
+            assert_eq!(error.contexts().len(), 1);
+            assert_eq!(error.contexts()[0].0.position(), 0);
+            assert_eq!(error.contexts()[0].1, "declaration");
+        }
+
+        #[test]
+        fn external_error() {
+            let source = Source::new("test.txt", "999");
+            let mut parser = map_res(ascii_numeric1, |digits: Span| {
+                digits.as_str().parse::<u8>()
+            });
+            let error: GraphemeError = match parser.parse(source.full_span()) {
+                Err($nom::Err::Error(error)) => error,
+                other => panic!("expected error, got {:?}", other),
+            };
+            assert_eq!(error.expected(), [Expected::Kind(ErrorKind::MapRes)]);
+            assert!(error.external().is_some());
+            assert!(std::error::Error::source(&error).is_some());
+        }
+    };
+}
+
+pub(crate) use error_tests;
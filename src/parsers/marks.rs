@@ -0,0 +1,145 @@
+//! Comparison ignoring diacritics, defined for each nom crate.
+
+#[cfg(test)]
+mod test;
+
+#[cfg(test)]
+pub(crate) use test::marks_tests;
+
+/// Defines a comparison that ignores diacritics, matching segments by their
+/// base characters, given the nom crate and its traits for the length of a
+/// tag and for taking input: `InputLength` and `InputTake` in nom 7, both
+/// merged into `Input` in nom 8.
+macro_rules! define_marks {
+    (
+        $nom:ident,
+        InputLength: $length:ident,
+        InputTake: $take:ident $(,)?
+    ) => {
+        #[cfg(test)]
+        mod test {
+            crate::parsers::marks_tests!($nom);
+        }
+
+        use crate::{
+            tag::{OwnedTag, Tag},
+            Span,
+            SpanContent,
+        };
+        use unicode_normalization::{
+            char::is_combining_mark,
+            UnicodeNormalization,
+        };
+        use $nom::error::{ErrorKind, ParseError};
+
+        /// Comparison of an input with a tag that ignores combining marks.
+        /// Segments are canonically decomposed and stripped of combining marks
+        /// before being compared, so `"n̩̊"` matches `"n"` and `"é"` matches
+        /// `"e"`. Segments are compared one by one, so a match always spans as
+        /// many segments as the tag.
+        pub trait CompareNoMarks<T> {
+            /// Compares this input with the given tag, ignoring combining
+            /// marks.
+            fn compare_no_marks(&self, tag: T) -> $nom::CompareResult;
+        }
+
+        impl<T> CompareNoMarks<T> for Span
+        where
+            T: IntoIterator,
+            T::Item: AsRef<str>,
+        {
+            fn compare_no_marks(&self, tag: T) -> $nom::CompareResult {
+                compare_segments(self.segments(), tag)
+            }
+        }
+
+        impl<T> CompareNoMarks<T> for SpanContent
+        where
+            T: IntoIterator,
+            T::Item: AsRef<str>,
+        {
+            fn compare_no_marks(&self, tag: T) -> $nom::CompareResult {
+                compare_segments(self.segments(), tag)
+            }
+        }
+
+        impl<'slice, 'seg, T> CompareNoMarks<T> for Tag<'slice, 'seg>
+        where
+            T: IntoIterator,
+            T::Item: AsRef<str>,
+        {
+            fn compare_no_marks(&self, tag: T) -> $nom::CompareResult {
+                compare_segments(self.segments(), tag)
+            }
+        }
+
+        impl<T> CompareNoMarks<T> for OwnedTag
+        where
+            T: IntoIterator,
+            T::Item: AsRef<str>,
+        {
+            fn compare_no_marks(&self, tag: T) -> $nom::CompareResult {
+                compare_segments(self.segments(), tag)
+            }
+        }
+
+        /// Recognizes the given tag ignoring combining marks (see
+        /// [`CompareNoMarks`]), e.g. `"n̩̊"` is recognized by the tag `"n"`.
+        pub fn tag_no_marks<T, I, E>(
+            tag: T,
+        ) -> impl Fn(I) -> $nom::IResult<I, I, E>
+        where
+            T: $nom::$length + Clone,
+            I: $nom::$take + CompareNoMarks<T>,
+            E: ParseError<I>,
+        {
+            move |input| match input.compare_no_marks(tag.clone()) {
+                $nom::CompareResult::Ok => {
+                    Ok(input.take_split(tag.input_len()))
+                },
+                _ => Err($nom::Err::Error(E::from_error_kind(
+                    input,
+                    ErrorKind::Tag,
+                ))),
+            }
+        }
+
+        /// Compares segments of an input with segments of a tag by their base
+        /// characters.
+        fn compare_segments<I, T>(input: I, tag: T) -> $nom::CompareResult
+        where
+            I: IntoIterator,
+            I::Item: AsRef<str>,
+            T: IntoIterator,
+            T::Item: AsRef<str>,
+        {
+            let mut input_iter = input.into_iter();
+            let mut tag_iter = tag.into_iter();
+
+            loop {
+                match (input_iter.next(), tag_iter.next()) {
+                    (Some(input_segment), Some(tag_segment)) => {
+                        if !eq_no_marks(
+                            input_segment.as_ref(),
+                            tag_segment.as_ref(),
+                        ) {
+                            break $nom::CompareResult::Error;
+                        }
+                    },
+                    (None, Some(_)) => break $nom::CompareResult::Incomplete,
+                    (_, None) => break $nom::CompareResult::Ok,
+                }
+            }
+        }
+
+        /// Tests whether the given strings are equal after canonical
+        /// decomposition and removal of combining marks.
+        fn eq_no_marks(left: &str, right: &str) -> bool {
+            left.nfd()
+                .filter(|&ch| !is_combining_mark(ch))
+                .eq(right.nfd().filter(|&ch| !is_combining_mark(ch)))
+        }
+    };
+}
+
+pub(crate) use define_marks;
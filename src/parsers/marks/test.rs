@@ -0,0 +1,42 @@
+/// Defines the tests of the comparison ignoring diacritics of a nom crate,
+/// given the crate.
+macro_rules! marks_tests {
+    ($nom:ident) => {
+        use super::{
+            super::{OwnedTag, Tag},
+            tag_no_marks,
+            CompareNoMarks,
+        };
+        use crate::{Source, Span};
+        use $nom::{CompareResult, IResult};
+
+        #[test]
+        fn compares_base_characters() {
+            let source = Source::new("foo.txt", "atn\u{329}\u{30a}é");
+            let span = source.full_span();
+            let tag = Tag(&["a", "t", "n", "e\u{301}"]);
+            assert_eq!(span.compare_no_marks(tag), CompareResult::Ok);
+            assert_eq!(
+                span.compare_no_marks(Tag(&["a", "d"])),
+                CompareResult::Error
+            );
+            let long = OwnedTag::new("atnee");
+            assert_eq!(span.compare_no_marks(&long), CompareResult::Incomplete);
+        }
+
+        #[test]
+        fn recognizes_tag() {
+            let source = Source::new("foo.txt", "n\u{329}\u{30a}o");
+            let result: IResult<Span, Span> =
+                tag_no_marks(Tag(&["n"]))(source.full_span());
+            let (rest, matched) = result.unwrap();
+            assert_eq!(matched.as_str(), "n\u{329}\u{30a}");
+            assert_eq!(rest.as_str(), "o");
+
+            let result: IResult<Span, Span> = tag_no_marks(Tag(&["n"]))(rest);
+            assert!(result.is_err());
+        }
+    };
+}
+
+pub(crate) use marks_tests;
@@ -0,0 +1,724 @@
+//! Parsers of segments/grapheme clusters, defined for each nom crate.
+
+#[cfg(test)]
+mod test;
+
+#[cfg(test)]
+pub(crate) use test::segment_tests;
+
+/// Defines the parsers of segments/grapheme clusters, given the nom crate and
+/// the input traits each parser requires. nom 7 splits these into
+/// `InputIter`, `InputLength`, `InputTake` and `InputTakeAtPosition`, while
+/// nom 8 merges them into `Input`.
+macro_rules! define_segment_parsers {
+    (
+        $nom:ident,
+        InputIter: $iter:ident,
+        InputLength: $length:ident,
+        InputTake: $take:ident,
+        InputTakeAtPosition: $at_position:ident $(,)?
+    ) => {
+        use crate::{tag::Tag, LocatedSegment};
+        use $nom::{
+            error::{ErrorKind, ParseError},
+            FindToken,
+        };
+
+        /// Recognizes zero or more UTF-8 alphabetic segments, possibly with
+        /// diacritics.
+        pub fn alpha0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_alphabetic())
+        }
+
+        /// Recognizes one or more UTF-8 alphabetic segments, possibly with
+        /// diacritics.
+        pub fn alpha1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_alphabetic(),
+                ErrorKind::Alpha,
+            )
+        }
+
+        /// Recognizes zero or more UTF-8 alphabetic segments without
+        /// diacritics.
+        pub fn char_alpha0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| {
+                !item.is_alphabetic() || !item.is_single_char()
+            })
+        }
+
+        /// Recognizes one or more UTF-8 alphabetic segments without diacritics.
+        pub fn char_alpha1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_alphabetic() || !item.is_single_char(),
+                ErrorKind::Alpha,
+            )
+        }
+
+        /// Recognizes zero or more ASCII alphabetic segments without
+        /// diacritics.
+        pub fn ascii_alpha0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_ascii_alphabetic())
+        }
+
+        /// Recognizes one or more ASCII alphabetic segments without diacritics.
+        pub fn ascii_alpha1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_ascii_alphabetic(),
+                ErrorKind::Alpha,
+            )
+        }
+
+        /// Recognizes zero or more UTF-8 alphanumeric segments, possibly with
+        /// diacritics.
+        pub fn alphanumeric0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_alphanumeric())
+        }
+
+        /// Recognizes one or more UTF-8 alphanumeric segments, possibly with
+        /// diacritics.
+        pub fn alphanumeric1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_alphanumeric(),
+                ErrorKind::AlphaNumeric,
+            )
+        }
+
+        /// Recognizes zero or more UTF-8 alphanumeric segments without
+        /// diacritics.
+        pub fn char_alphanumeric0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| {
+                !item.is_alphanumeric() || !item.is_single_char()
+            })
+        }
+
+        /// Recognizes one or more UTF-8 alphanumeric segments without
+        /// diacritics.
+        pub fn char_alphanumeric1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_alphanumeric() || !item.is_single_char(),
+                ErrorKind::AlphaNumeric,
+            )
+        }
+
+        /// Recognizes zero or more ASCII alphanumeric segments without
+        /// diacritics.
+        pub fn ascii_alphanumeric0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| {
+                !item.is_ascii_alphanumeric()
+            })
+        }
+
+        /// Recognizes one or more ASCII alphanumeric segments without
+        /// diacritics.
+        pub fn ascii_alphanumeric1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_ascii_alphanumeric(),
+                ErrorKind::AlphaNumeric,
+            )
+        }
+
+        /// Recognizes zero or more UTF-8 numeric segments, possibly with
+        /// diacritics.
+        pub fn numeric0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_numeric())
+        }
+
+        /// Recognizes one or more UTF-8 numeric segments, possibly with
+        /// diacritics.
+        pub fn numeric1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_numeric(),
+                ErrorKind::Digit,
+            )
+        }
+
+        /// Recognizes zero or more UTF-8 numeric segments without diacritics.
+        pub fn char_numeric0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| {
+                !item.is_numeric() || !item.is_single_char()
+            })
+        }
+
+        /// Recognizes one or more UTF-8 numeric segments without diacritics.
+        pub fn char_numeric1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_numeric() || !item.is_single_char(),
+                ErrorKind::Digit,
+            )
+        }
+
+        /// Recognizes zero or more ASCII numeric segments without diacritics.
+        pub fn ascii_numeric0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_ascii_numeric())
+        }
+
+        /// Recognizes one or more ASCII numeric segments without diacritics.
+        pub fn ascii_numeric1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_ascii_numeric(),
+                ErrorKind::Digit,
+            )
+        }
+
+        /// Recognizes zero or more digits in the given base. ASCII characters
+        /// `0-9`. `a-z`, `A-Z` are considered digits, depending on the base.
+        pub fn digit0<T, E>(
+            base: u32,
+        ) -> impl FnMut(T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            move |input| {
+                input.split_at_position_complete(|item| item.is_digit(base))
+            }
+        }
+
+        /// Recognizes one or more digits in the given base. ASCII characters
+        /// `0-9`. `a-z`, `A-Z` are considered digits, depending on the base.
+        pub fn digit1<T, E>(
+            base: u32,
+        ) -> impl FnMut(T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            move |input| {
+                input.split_at_position1_complete(
+                    |item| item.is_digit(base),
+                    match base {
+                        8 => ErrorKind::OctDigit,
+                        16 => ErrorKind::HexDigit,
+                        _ => ErrorKind::Digit,
+                    },
+                )
+            }
+        }
+
+        /// Recognizes zero or more unicode whitespace graphemes.
+        pub fn whitespace0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_whitespace())
+        }
+
+        /// Recognizes one or more unicode whitespace graphemes.
+        pub fn whitespace1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_whitespace(),
+                ErrorKind::Space,
+            )
+        }
+
+        /// Recognizes zero or more ASCII spaces.
+        pub fn space0<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position_complete(|item| !item.is_space())
+        }
+
+        /// Recognizes one or more ASCII spaces.
+        pub fn space1<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            E: ParseError<T>,
+        {
+            input.split_at_position1_complete(
+                |item| !item.is_space(),
+                ErrorKind::Space,
+            )
+        }
+
+        /// Recognizes one tab (`"\t"`) ASCII character.
+        pub fn tab<T, E>(input: T) -> $nom::IResult<T, T::Item, E>
+        where
+            T: $nom::$iter + $nom::$length + $nom::$take,
+            for<'tok> T::Item: PartialEq<&'tok str>,
+            E: ParseError<T>,
+        {
+            segment("\t")(input)
+        }
+
+        /// Recognizes one linefeed (`"\n"`) ASCII character.
+        pub fn newline<T, E>(input: T) -> $nom::IResult<T, T::Item, E>
+        where
+            T: $nom::$iter + $nom::$length + $nom::$take,
+            for<'tok> T::Item: PartialEq<&'tok str>,
+            E: ParseError<T>,
+        {
+            segment("\n")(input)
+        }
+
+        /// Recognizes the sequence `"\r\n"`, either as a single grapheme
+        /// cluster or, e.g. when segmenting into chars, as two segments.
+        pub fn crlf<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$take,
+            for<'slice, 'seg> T: $nom::Compare<Tag<'slice, 'seg>>,
+            E: ParseError<T>,
+        {
+            for tag in [Tag(&["\r\n"]), Tag(&["\r", "\n"])] {
+                if input.compare(tag) == $nom::CompareResult::Ok {
+                    return Ok(input.take_split(tag.len()));
+                }
+            }
+            Err($nom::Err::Error(E::from_error_kind(input, ErrorKind::CrLf)))
+        }
+
+        /// Parses line ending, either a linefeed or a `"\r\n"` sequence.
+        pub fn line_ending<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$take,
+            for<'slice, 'seg> T: $nom::Compare<Tag<'slice, 'seg>>,
+            E: ParseError<T>,
+        {
+            match input.compare(Tag(&["\n"])) {
+                $nom::CompareResult::Ok => Ok(input.take_split(1)),
+                $nom::CompareResult::Error => crlf(input),
+                $nom::CompareResult::Incomplete => Err($nom::Err::Error(
+                    E::from_error_kind(input, ErrorKind::CrLf),
+                )),
+            }
+        }
+
+        /// Parses segments until a line ending (`"\n"` or `"\r\n"`) is found.
+        pub fn not_line_ending<T, E>(input: T) -> $nom::IResult<T, T, E>
+        where
+            T: $nom::$take + $nom::$iter,
+            for<'tok> T::Item: PartialEq<&'tok str>,
+            E: ParseError<T>,
+        {
+            let mut previous_car = false;
+
+            for (i, segment) in input.iter_indices() {
+                if segment == "\n" {
+                    let split_index = i - 1 - usize::from(previous_car);
+                    return Ok(input.take_split(split_index));
+                }
+                previous_car = segment == "\r";
+            }
+
+            Err($nom::Err::Error(E::from_error_kind(input, ErrorKind::Tag)))
+        }
+
+        /// Recognizes the given grapheme cluster/segment.
+        pub fn segment<T, A, E>(
+            expected: A,
+        ) -> impl FnMut(T) -> $nom::IResult<T, T::Item, E>
+        where
+            T: $nom::$iter + $nom::$length + $nom::$take,
+            T::Item: PartialEq<A>,
+            E: ParseError<T>,
+        {
+            move |input| {
+                let mut iterator = input.iter_indices();
+                match iterator.next() {
+                    Some((_, segment)) => {
+                        if segment == expected {
+                            match iterator.next() {
+                                Some((index, _)) => {
+                                    Ok((input.take_split(index).0, segment))
+                                },
+                                None => Ok((
+                                    input.take_split(input.input_len()).0,
+                                    segment,
+                                )),
+                            }
+                        } else {
+                            Err($nom::Err::Error(E::from_error_kind(
+                                input,
+                                ErrorKind::IsA,
+                            )))
+                        }
+                    },
+                    None => Err($nom::Err::Error(E::from_error_kind(
+                        input,
+                        ErrorKind::Eof,
+                    ))),
+                }
+            }
+        }
+
+        /// Recognizes any grapheme cluster/segment.
+        pub fn any_segment<T, E>(
+            input: T,
+        ) -> $nom::IResult<T, LocatedSegment, E>
+        where
+            T: $nom::$iter<Item = LocatedSegment> + $nom::$length + $nom::$take,
+            E: ParseError<T>,
+        {
+            let mut iterator = input.iter_indices();
+            match iterator.next() {
+                Some((_, segment)) => match iterator.next() {
+                    Some((index, _)) => {
+                        Ok((input.take_split(index).0, segment))
+                    },
+                    None => {
+                        Ok((input.take_split(input.input_len()).0, segment))
+                    },
+                },
+                None => Err($nom::Err::Error(E::from_error_kind(
+                    input,
+                    ErrorKind::Eof,
+                ))),
+            }
+        }
+
+        /// Recognizes any of the grapheme clusters/segments in the given list.
+        pub fn one_of<T, L, E>(
+            list: L,
+        ) -> impl FnMut(T) -> $nom::IResult<T, T::Item, E>
+        where
+            T: $nom::$iter + $nom::$length + $nom::$take,
+            for<'tok> L: FindToken<&'tok T::Item>,
+            E: ParseError<T>,
+        {
+            move |input| {
+                let mut iterator = input.iter_indices();
+                match iterator.next() {
+                    Some((_, segment)) => {
+                        if list.find_token(&segment) {
+                            match iterator.next() {
+                                Some((index, _)) => {
+                                    Ok((input.take_split(index).0, segment))
+                                },
+                                None => Ok((
+                                    input.take_split(input.input_len()).0,
+                                    segment,
+                                )),
+                            }
+                        } else {
+                            Err($nom::Err::Error(E::from_error_kind(
+                                input,
+                                ErrorKind::IsNot,
+                            )))
+                        }
+                    },
+                    None => Err($nom::Err::Error(E::from_error_kind(
+                        input,
+                        ErrorKind::Eof,
+                    ))),
+                }
+            }
+        }
+
+        /// Recognizes a grapheme clusters/segments NOT in the given list.
+        pub fn none_of<T, L, E>(
+            list: L,
+        ) -> impl FnMut(T) -> $nom::IResult<T, T::Item, E>
+        where
+            T: $nom::$iter + $nom::$length + $nom::$take,
+            for<'tok> L: FindToken<&'tok T::Item>,
+            E: ParseError<T>,
+        {
+            move |input| {
+                let mut iterator = input.iter_indices();
+                match iterator.next() {
+                    Some((_, segment)) => {
+                        if !list.find_token(&segment) {
+                            match iterator.next() {
+                                Some((index, _)) => {
+                                    Ok((input.take_split(index).0, segment))
+                                },
+                                None => Ok((
+                                    input.take_split(input.input_len()).0,
+                                    segment,
+                                )),
+                            }
+                        } else {
+                            Err($nom::Err::Error(E::from_error_kind(
+                                input,
+                                ErrorKind::IsNot,
+                            )))
+                        }
+                    },
+                    None => Err($nom::Err::Error(E::from_error_kind(
+                        input,
+                        ErrorKind::Eof,
+                    ))),
+                }
+            }
+        }
+
+        /// Recognizes a character that satifies the given `condition` function.
+        pub fn satisfy<F, T, E>(
+            mut condition: F,
+        ) -> impl FnMut(T) -> $nom::IResult<T, T::Item, E>
+        where
+            for<'item> F: FnMut(&'item T::Item) -> bool,
+            T: $nom::$take + $nom::$iter,
+            E: ParseError<T>,
+        {
+            move |input| match input.iter_elements().next() {
+                Some(elem) if condition(&elem) => {
+                    Ok((input.take_split(1).0, elem))
+                },
+                _ => Err($nom::Err::Error(E::from_error_kind(
+                    input,
+                    ErrorKind::Satisfy,
+                ))),
+            }
+        }
+
+        crate::parsers::parse_unsigned_int! {
+            $nom,
+            $at_position,
+            digits_u8,
+            u8,
+            /// Parses an unsigned 8-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_unsigned_int! {
+            $nom,
+            $at_position,
+            digits_u16,
+            u16,
+            /// Parses an unsigned 16-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_unsigned_int! {
+            $nom,
+            $at_position,
+            digits_u32,
+            u32,
+            /// Parses an unsigned 32-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_unsigned_int! {
+            $nom,
+            $at_position,
+            digits_u64,
+            u64,
+            /// Parses an unsigned 64-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_unsigned_int! {
+            $nom,
+            $at_position,
+            digits_u128,
+            u128,
+            /// Parses an unsigned 128-bit number. Consumes all available
+            /// digits, but might return an error if too large.
+        }
+
+        crate::parsers::parse_signed_int! {
+            $nom,
+            $iter,
+            $length,
+            $take,
+            $at_position,
+            digits_i8,
+            i8,
+            /// Parses a signed 8-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_signed_int! {
+            $nom,
+            $iter,
+            $length,
+            $take,
+            $at_position,
+            digits_i16,
+            i16,
+            /// Parses a signed 16-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_signed_int! {
+            $nom,
+            $iter,
+            $length,
+            $take,
+            $at_position,
+            digits_i32,
+            i32,
+            /// Parses a signed 32-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_signed_int! {
+            $nom,
+            $iter,
+            $length,
+            $take,
+            $at_position,
+            digits_i64,
+            i64,
+            /// Parses a signed 64-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+
+        crate::parsers::parse_signed_int! {
+            $nom,
+            $iter,
+            $length,
+            $take,
+            $at_position,
+            digits_i128,
+            i128,
+            /// Parses a signed 128-bit number. Consumes all available digits,
+            /// but might return an error if too large.
+        }
+    };
+}
+
+pub(crate) use define_segment_parsers;
+
+macro_rules! parse_unsigned_int {
+    (
+        $nom:ident,
+        $at_position:ident,
+        $fn_name:ident,
+        $ty:ty,
+        $($doc:tt)*
+    ) => {
+        $($doc)*
+        pub fn $fn_name<T, E>(base: u32) -> impl FnMut(T) -> $nom::IResult<T, $ty, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment>,
+            T: AsRef<str> + Clone,
+            E: ParseError<T>,
+        {
+            move |input0| {
+                let (input1, digits) =  digit1(base)(input0.clone())?;
+                match <$ty>::from_str_radix(digits.as_ref(), base) {
+                    Ok(num) => Ok((input1, num)),
+                    Err(_) => Err($nom::Err::Error(E::from_error_kind(
+                        input0,
+                        ErrorKind::TooLarge
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use parse_unsigned_int;
+
+macro_rules! parse_signed_int {
+    (
+        $nom:ident,
+        $iter:ident,
+        $length:ident,
+        $take:ident,
+        $at_position:ident,
+        $fn_name:ident,
+        $ty:ty,
+        $($doc:tt)*
+    ) => {
+        $($doc)*
+        pub fn $fn_name<T, E>(base: u32) -> impl FnMut(T) -> $nom::IResult<T, $ty, E>
+        where
+            T: $nom::$at_position<Item = LocatedSegment> + $nom::$take,
+            T: $nom::$length + $nom::$iter<Item = LocatedSegment>,
+            T: AsRef<str> + Clone,
+            E: ParseError<T>,
+        {
+            move |input0: T| {
+                let sign_len = match input0.iter_elements().next() {
+                    Some(segment) if segment == "+" || segment == "-" => 1,
+                    _ => 0,
+                };
+                let (_, digits) = digit1(base)(input0.take_split(sign_len).0)?;
+                let (input1, number) =
+                    input0.take_split(sign_len + digits.input_len());
+
+                match <$ty>::from_str_radix(number.as_ref(), base) {
+                    Ok(num) => Ok((input1, num)),
+                    Err(_) => Err($nom::Err::Error(E::from_error_kind(
+                        input0,
+                        ErrorKind::TooLarge
+                    ))),
+                }
+            }
+        }
+    };
+}
+
+pub(crate) use parse_signed_int;
@@ -0,0 +1,116 @@
+/// Defines the tests of the parsers of a nom crate, given the crate.
+macro_rules! segment_tests {
+    ($nom:ident) => {
+        use super::{
+            alpha1,
+            crlf,
+            line_ending,
+            one_of,
+            satisfy,
+            segment,
+            symbol,
+            whitespace1,
+            Expected,
+            GraphemeError,
+            Tag,
+        };
+        use crate::{LocatedSegment, Source, Span};
+        use $nom::{branch::alt, error::ErrorKind, IResult, Offset, Parser};
+
+        #[test]
+        fn alphabetic_with_diacritics() {
+            let source = Source::new("foo.txt", "atn\u{329}\u{30a} 12");
+            let result: IResult<Span, Span> = alpha1(source.full_span());
+            let (rest, word) = result.unwrap();
+            assert_eq!(word.as_str(), "atn\u{329}\u{30a}");
+            let result: IResult<Span, Span> = whitespace1(rest);
+            let (rest, _) = result.unwrap();
+            let result: IResult<Span, Span> = alpha1(rest);
+            match result {
+                Err($nom::Err::Error(error)) => {
+                    assert_eq!(error.code, ErrorKind::Alpha)
+                },
+                other => panic!("expected error, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn line_endings() {
+            let source = Source::new("foo.txt", "\r\n\nab");
+            let result: IResult<Span, Span> = crlf(source.full_span());
+            let (rest, ending) = result.unwrap();
+            assert_eq!(ending.as_str(), "\r\n");
+            let result: IResult<Span, Span> = crlf(rest.clone());
+            match result {
+                Err($nom::Err::Error(error)) => {
+                    assert_eq!(error.code, ErrorKind::CrLf)
+                },
+                other => panic!("expected error, got {:?}", other),
+            }
+            let result: IResult<Span, Span> = line_ending(rest);
+            let (rest, ending) = result.unwrap();
+            assert_eq!(ending.as_str(), "\n");
+            let result: IResult<Span, Span> = line_ending(rest);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn single_segments() {
+            let source = Source::new("foo.txt", "e\u{301}xy");
+            let result: IResult<Span, LocatedSegment> =
+                segment("e\u{301}")(source.full_span());
+            let (rest, parsed) = result.unwrap();
+            assert_eq!(parsed, "e\u{301}");
+            let result: IResult<Span, LocatedSegment> =
+                one_of(Tag(&["y", "x"]))(rest);
+            let (rest, parsed) = result.unwrap();
+            assert_eq!(parsed, "x");
+            let result: IResult<Span, LocatedSegment> = satisfy(
+                |segment: &LocatedSegment| segment.is_alphabetic(),
+            )(rest);
+            let (rest, parsed) = result.unwrap();
+            assert_eq!(parsed, "y");
+            let result: IResult<Span, LocatedSegment> =
+                one_of(Tag(&["y"]))(rest);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn symbols() {
+            let source = Source::new("foo.txt", "let x");
+            let mut parser = symbol(Tag(&["l", "e", "t"]).expect());
+            let result: IResult<Span, _, GraphemeError> =
+                parser(source.full_span());
+            let (rest, symbol) = result.unwrap();
+            assert_eq!(symbol.data.as_str(), "let");
+            assert_eq!(rest.as_str(), " x");
+        }
+
+        #[test]
+        fn tag_parsers() {
+            let source = Source::new("foo.txt", "fn x");
+            let mut keyword = alt((Tag(&["l", "e", "t"]), Tag(&["f", "n"])));
+            let result: IResult<Span, Span> = keyword.parse(source.full_span());
+            let (rest, parsed) = result.unwrap();
+            assert_eq!(parsed.as_str(), "fn");
+
+            let mut keyword = Tag(&["l", "e", "t"]).expect();
+            let error: GraphemeError = match keyword.parse(rest) {
+                Err($nom::Err::Error(error)) => error,
+                other => panic!("expected error, got {:?}", other),
+            };
+            assert_eq!(error.location().position(), 2);
+            assert_eq!(error.expected(), [Expected::Tag("let".into())]);
+        }
+
+        #[test]
+        fn tag_offset() {
+            let segments = ["l", "e", "t"];
+            let tag = Tag(&segments);
+            assert_eq!(tag.offset(&Tag(&segments[2 ..])), 2);
+            assert_eq!(tag.offset(&tag), 0);
+        }
+    };
+}
+
+pub(crate) use segment_tests;
@@ -0,0 +1,499 @@
+//! This module provides serialization of source code objects, locations, spans
+//! and symbols with `serde`.
+//!
+//! Locations and spans are serialized as the name of their source together
+//! with segment positions, lines, columns and byte offsets. Since a location
+//! is meaningless without its source, they are deserialized through seeds
+//! ([`LocationSeed`], [`SpanSeed`] and [`SymbolSeed`]) holding a
+//! [`SourceResolver`] that finds sources by name, such as a [`Source`] or a
+//! [`SourceMap`].
+//!
+//! Sources are serialized with their options, and deserialized with them,
+//! unless split by a custom segmenter: such sources are deserialized through a
+//! [`SourceSeed`] holding a [`SourceBuilder`] with the segmenter.
+//!
+//! # Examples
+//! ```
+//! use nom_grapheme_clusters::{serialization::SpanSeed, Source};
+//! use serde::de::DeserializeSeed;
+//!
+//! let source = Source::new("main.txt", "let x = 1;");
+//! let span = source.full_span().try_slice(4 .. 5).unwrap();
+//! let json = serde_json::to_string(&span).unwrap();
+//!
+//! let mut deserializer = serde_json::Deserializer::from_str(&json);
+//! let deserialized = SpanSeed::new(&source).deserialize(&mut deserializer);
+//! assert_eq!(deserialized.unwrap(), span);
+//! ```
+
+#[cfg(test)]
+mod test;
+
+use crate::{
+    location::Location,
+    source::{
+        CaseFolding,
+        LineBreakPolicy,
+        NormalizationForm,
+        Segmentation,
+        Source,
+        SourceBuilder,
+        SourceMap,
+    },
+    span::{Span, Symbol},
+};
+use serde::{
+    de::DeserializeSeed,
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+};
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+/// Finds source code objects by name when deserializing locations and spans.
+pub trait SourceResolver {
+    /// Returns the source with the given name, if any.
+    fn resolve(&self, name: &str) -> Option<Source>;
+}
+
+impl SourceResolver for Source {
+    fn resolve(&self, name: &str) -> Option<Source> {
+        if self.name() == name {
+            Some(self.clone())
+        } else {
+            None
+        }
+    }
+}
+
+impl SourceResolver for SourceMap {
+    fn resolve(&self, name: &str) -> Option<Source> {
+        self.get_by_name(name).cloned()
+    }
+}
+
+impl<R> SourceResolver for &R
+where
+    R: SourceResolver + ?Sized,
+{
+    fn resolve(&self, name: &str) -> Option<Source> {
+        (**self).resolve(name)
+    }
+}
+
+/// A location without its source, as serialized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct Point {
+    /// Position in grapheme clusters/segments.
+    position: usize,
+    /// Line, starting from `0`.
+    line: usize,
+    /// Column in grapheme clusters/segments, starting from `0`.
+    column: usize,
+    /// Offset in bytes.
+    byte_offset: usize,
+}
+
+impl Point {
+    /// Gathers the data of a location.
+    fn new(location: &Location) -> Self {
+        let (line, column) = location.line_column();
+        Self {
+            position: location.position(),
+            line,
+            column,
+            byte_offset: location.byte_offset(),
+        }
+    }
+
+    /// Finds the location of this point in the given source, checking that
+    /// the source still has the same contents.
+    fn locate<E>(self, source: &Source) -> Result<Location, E>
+    where
+        E: serde::de::Error,
+    {
+        let location = source
+            .full_span()
+            .try_slice(self.position ..)
+            .map(|span| span.start())
+            .ok_or_else(|| {
+                E::custom(format_args!(
+                    "position {} is out of bounds of {}",
+                    self.position, source
+                ))
+            })?;
+        if Self::new(&location) != self {
+            return Err(E::custom(format_args!(
+                "position {} does not match the contents of {}",
+                self.position, source
+            )));
+        }
+        Ok(location)
+    }
+}
+
+/// Resolves the source of the given name.
+fn resolve<R, E>(resolver: &R, name: &str) -> Result<Source, E>
+where
+    R: SourceResolver + ?Sized,
+    E: serde::de::Error,
+{
+    resolver
+        .resolve(name)
+        .ok_or_else(|| E::custom(format_args!("unknown source {:?}", name)))
+}
+
+/// A location, as serialized. The fields of [`Point`] are spelled out rather
+/// than flattened, since flattening requires a self-describing format.
+#[derive(Serialize, Deserialize)]
+struct LocationRepr<'name> {
+    /// Name of the source.
+    #[serde(borrow)]
+    file: Cow<'name, str>,
+    /// Position in grapheme clusters/segments.
+    position: usize,
+    /// Line, starting from `0`.
+    line: usize,
+    /// Column in grapheme clusters/segments, starting from `0`.
+    column: usize,
+    /// Offset in bytes.
+    byte_offset: usize,
+}
+
+impl<'name> LocationRepr<'name> {
+    /// Gathers the data of a location.
+    fn new(location: &'name Location) -> Self {
+        let Point { position, line, column, byte_offset } =
+            Point::new(location);
+        Self {
+            file: location.source().name().into(),
+            position,
+            line,
+            column,
+            byte_offset,
+        }
+    }
+
+    /// Where in the source the location is.
+    fn point(&self) -> Point {
+        Point {
+            position: self.position,
+            line: self.line,
+            column: self.column,
+            byte_offset: self.byte_offset,
+        }
+    }
+}
+
+/// A span, as serialized.
+#[derive(Serialize, Deserialize)]
+struct SpanRepr<'name> {
+    /// Name of the source.
+    #[serde(borrow)]
+    file: Cow<'name, str>,
+    /// Start of the span.
+    start: Point,
+    /// End of the span (exclusive).
+    end: Point,
+}
+
+impl<'name> SpanRepr<'name> {
+    /// Resolves the span against the sources of a resolver.
+    fn resolve<R, E>(self, resolver: &R) -> Result<Span, E>
+    where
+        R: SourceResolver + ?Sized,
+        E: serde::de::Error,
+    {
+        let source = resolve(resolver, &self.file)?;
+        let start = self.start.locate(&source)?;
+        let end = self.end.locate(&source)?;
+        if end.position() < start.position() {
+            return Err(E::custom("span starts past beyond its end"));
+        }
+        Ok(Span::from_range(start, end))
+    }
+}
+
+/// A source code object, as serialized.
+#[derive(Serialize, Deserialize)]
+struct SourceRepr<'src> {
+    /// Name of the source.
+    #[serde(borrow)]
+    name: Cow<'src, str>,
+    /// Contents of the source, before normalization.
+    #[serde(borrow)]
+    contents: Cow<'src, str>,
+    /// Built-in segmentation, or `None` for a custom segmenter.
+    segmentation: Option<Segmentation>,
+    /// Policy used to find line breaks.
+    line_break_policy: LineBreakPolicy,
+    /// Case folding used by case-insensitive comparisons.
+    case_folding: CaseFolding,
+    /// Form the contents are normalized into, if any.
+    normalization: Option<NormalizationForm>,
+}
+
+impl<'src> SourceRepr<'src> {
+    /// Creates the source with the given builder, normalizing the contents
+    /// if they were normalized before.
+    fn finish(self, builder: &SourceBuilder) -> Source {
+        let name = self.name.into_owned();
+        let contents = self.contents.into_owned();
+        match self.normalization {
+            Some(form) => builder.finish_normalized(name, contents, form),
+            None => builder.finish(name, contents),
+        }
+    }
+}
+
+/// Sources are serialized with their name, their contents before
+/// normalization and their options: segmentation, line-break policy, case
+/// folding and normalization form.
+impl Serialize for Source {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SourceRepr {
+            name: self.name().into(),
+            contents: self.original_contents().into(),
+            segmentation: self.segmentation(),
+            line_break_policy: self.line_break_policy(),
+            case_folding: self.case_folding(),
+            normalization: self.normalization_form(),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Sources are deserialized with the options they were serialized with.
+/// Sources split by a custom [`Segmenter`](crate::source::Segmenter) cannot
+/// be, since the segmenter is not serialized: use a [`SourceSeed`] instead.
+impl<'de> Deserialize<'de> for Source {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = SourceRepr::deserialize(deserializer)?;
+        let segmentation = repr.segmentation.ok_or_else(|| {
+            serde::de::Error::custom(format_args!(
+                "source {:?} has a custom segmenter",
+                repr.name
+            ))
+        })?;
+        let mut builder = SourceBuilder::new();
+        builder
+            .segmenter(segmentation)
+            .line_break_policy(repr.line_break_policy)
+            .case_folding(repr.case_folding);
+        Ok(repr.finish(&builder))
+    }
+}
+
+impl Serialize for Location {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        LocationRepr::new(self).serialize(serializer)
+    }
+}
+
+impl Serialize for Span {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SpanRepr {
+            file: self.source().name().into(),
+            start: Point::new(&self.start()),
+            end: Point::new(&self.end()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<T> Serialize for Symbol<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        SymbolRepr { span: &self.span, data: &self.data }.serialize(serializer)
+    }
+}
+
+/// A symbol, as serialized.
+#[derive(Serialize, Deserialize)]
+struct SymbolRepr<S, T> {
+    /// The span of the symbol.
+    span: S,
+    /// The data of the symbol.
+    data: T,
+}
+
+/// Deserializes a [`Location`], resolving its source by name and checking
+/// that its line, column and byte offset still match the source.
+pub struct LocationSeed<'res, R>
+where
+    R: ?Sized,
+{
+    /// Finds sources by name.
+    resolver: &'res R,
+}
+
+impl<'res, R> LocationSeed<'res, R>
+where
+    R: SourceResolver + ?Sized,
+{
+    /// Creates a seed resolving sources through the given resolver.
+    pub fn new(resolver: &'res R) -> Self {
+        Self { resolver }
+    }
+}
+
+impl<'res, R> fmt::Debug for LocationSeed<'res, R>
+where
+    R: ?Sized,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("LocationSeed").finish_non_exhaustive()
+    }
+}
+
+impl<'res, 'de, R> DeserializeSeed<'de> for LocationSeed<'res, R>
+where
+    R: SourceResolver + ?Sized,
+{
+    type Value = Location;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = LocationRepr::deserialize(deserializer)?;
+        let source = resolve(self.resolver, &repr.file)?;
+        repr.point().locate(&source)
+    }
+}
+
+/// Deserializes a [`Span`], resolving its source by name and checking that
+/// its bounds still match the source.
+pub struct SpanSeed<'res, R>
+where
+    R: ?Sized,
+{
+    /// Finds sources by name.
+    resolver: &'res R,
+}
+
+impl<'res, R> SpanSeed<'res, R>
+where
+    R: SourceResolver + ?Sized,
+{
+    /// Creates a seed resolving sources through the given resolver.
+    pub fn new(resolver: &'res R) -> Self {
+        Self { resolver }
+    }
+}
+
+impl<'res, R> fmt::Debug for SpanSeed<'res, R>
+where
+    R: ?Sized,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("SpanSeed").finish_non_exhaustive()
+    }
+}
+
+impl<'res, 'de, R> DeserializeSeed<'de> for SpanSeed<'res, R>
+where
+    R: SourceResolver + ?Sized,
+{
+    type Value = Span;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        SpanRepr::deserialize(deserializer)?.resolve(self.resolver)
+    }
+}
+
+/// Deserializes a [`Symbol`], resolving the source of its span as in
+/// [`SpanSeed`]. The data is deserialized with its [`Deserialize`] impl.
+pub struct SymbolSeed<'res, R, T>
+where
+    R: ?Sized,
+{
+    /// Finds sources by name.
+    resolver: &'res R,
+    /// The type of the data.
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<'res, R, T> SymbolSeed<'res, R, T>
+where
+    R: SourceResolver + ?Sized,
+{
+    /// Creates a seed resolving sources through the given resolver.
+    pub fn new(resolver: &'res R) -> Self {
+        Self { resolver, _marker: PhantomData }
+    }
+}
+
+impl<'res, R, T> fmt::Debug for SymbolSeed<'res, R, T>
+where
+    R: ?Sized,
+{
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("SymbolSeed").finish_non_exhaustive()
+    }
+}
+
+impl<'res, 'de, R, T> DeserializeSeed<'de> for SymbolSeed<'res, R, T>
+where
+    R: SourceResolver + ?Sized,
+    T: Deserialize<'de>,
+{
+    type Value = Symbol<T>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = SymbolRepr::<SpanRepr, T>::deserialize(deserializer)?;
+        Ok(Symbol { span: repr.span.resolve(self.resolver)?, data: repr.data })
+    }
+}
+
+/// Deserializes a [`Source`] with the options of a [`SourceBuilder`] rather
+/// than the serialized ones, e.g. to split it with a custom
+/// [`Segmenter`](crate::source::Segmenter). The contents are still normalized
+/// if they were before serialization.
+#[derive(Debug, Clone, Copy)]
+pub struct SourceSeed<'build> {
+    /// Options of the source.
+    builder: &'build SourceBuilder,
+}
+
+impl<'build> SourceSeed<'build> {
+    /// Creates a seed building sources with the given builder.
+    pub fn new(builder: &'build SourceBuilder) -> Self {
+        Self { builder }
+    }
+}
+
+impl<'build, 'de> DeserializeSeed<'de> for SourceSeed<'build> {
+    type Value = Source;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(SourceRepr::deserialize(deserializer)?.finish(self.builder))
+    }
+}
@@ -0,0 +1,156 @@
+use super::{LocationSeed, SourceSeed, SpanSeed, SymbolSeed};
+use crate::{
+    source::{
+        CaseFolding,
+        LineBreakPolicy,
+        NormalizationForm,
+        SegmentIndices,
+        Segmentation,
+        Segmenter,
+        SourceBuilder,
+    },
+    span::Symbol,
+    Source,
+    SourceMap,
+};
+use bincode::Options;
+use serde::de::DeserializeSeed;
+use serde_json::{json, Deserializer};
+
+#[test]
+fn location_round_trip() {
+    let source = Source::new("test.txt", "a\u{301}b\nc");
+    let location = source.full_span().try_slice(3 ..).unwrap().start();
+    let value = serde_json::to_value(&location).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "file": "test.txt",
+            "position": 3,
+            "line": 1,
+            "column": 0,
+            "byte_offset": 5,
+        })
+    );
+    let json = value.to_string();
+    let seed = LocationSeed::new(&source);
+    let deserialized =
+        seed.deserialize(&mut Deserializer::from_str(&json)).unwrap();
+    assert_eq!(deserialized, location);
+}
+
+#[test]
+fn location_non_self_describing() {
+    let source = Source::new("test.txt", "a\u{301}b\nc");
+    let location = source.full_span().try_slice(3 ..).unwrap().start();
+    let bytes = bincode::serialize(&location).unwrap();
+    let seed = LocationSeed::new(&source);
+    let deserialized = bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .deserialize_seed(seed, &bytes)
+        .unwrap();
+    assert_eq!(deserialized, location);
+}
+
+#[test]
+fn symbol_through_source_map() {
+    let mut map = SourceMap::new();
+    map.add("a.txt", "foo");
+    let id = map.add("b.txt", "bar baz");
+    let span = map[id].full_span().try_slice(4 ..).unwrap();
+    let symbol = Symbol { span, data: 42u32 };
+    let json = serde_json::to_string(&symbol).unwrap();
+    let seed = SymbolSeed::<_, u32>::new(&map);
+    let deserialized =
+        seed.deserialize(&mut Deserializer::from_str(&json)).unwrap();
+    assert_eq!(deserialized.span, symbol.span);
+    assert_eq!(deserialized.data, 42);
+}
+
+#[test]
+fn stale_or_unknown_source() {
+    let old = Source::new("test.txt", "héllo");
+    let span = old.full_span().try_slice(2 ..).unwrap();
+    let json = serde_json::to_string(&span).unwrap();
+
+    let new = Source::new("test.txt", "hello");
+    let seed = SpanSeed::new(&new);
+    assert!(seed.deserialize(&mut Deserializer::from_str(&json)).is_err());
+
+    let other = Source::new("other.txt", "héllo");
+    let seed = SpanSeed::new(&other);
+    assert!(seed.deserialize(&mut Deserializer::from_str(&json)).is_err());
+}
+
+#[test]
+fn source_round_trip() {
+    let source = Source::new("test.txt", "abc");
+    let value = serde_json::to_value(&source).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "name": "test.txt",
+            "contents": "abc",
+            "segmentation": "Extended",
+            "line_break_policy": "Unicode",
+            "case_folding": "Default",
+            "normalization": null,
+        })
+    );
+    let deserialized: Source = serde_json::from_value(value).unwrap();
+    assert_eq!(deserialized.name(), "test.txt");
+    assert_eq!(deserialized.contents(), "abc");
+}
+
+#[test]
+fn source_options_round_trip() {
+    let mut builder = SourceBuilder::new();
+    builder
+        .segmenter(Segmentation::Words)
+        .line_break_policy(LineBreakPolicy::Ascii)
+        .case_folding(CaseFolding::Turkic);
+    let source = builder.finish_normalized(
+        "test.txt",
+        "e\u{301}cole\rIstanbul",
+        NormalizationForm::Nfc,
+    );
+    let bytes = bincode::serialize(&source).unwrap();
+    let deserialized: Source = bincode::deserialize(&bytes).unwrap();
+    assert_eq!(deserialized.name(), "test.txt");
+    assert_eq!(deserialized.contents(), source.contents());
+    assert_eq!(deserialized.original_contents(), "e\u{301}cole\rIstanbul");
+    assert_eq!(deserialized.segmentation(), Some(Segmentation::Words));
+    assert_eq!(deserialized.line_break_policy(), LineBreakPolicy::Ascii);
+    assert_eq!(deserialized.case_folding(), CaseFolding::Turkic);
+    assert_eq!(deserialized.normalization_form(), Some(NormalizationForm::Nfc));
+    assert_eq!(deserialized.len(), source.len());
+    assert_eq!(deserialized.line_count(), 2);
+}
+
+#[test]
+fn custom_segmenter_source() {
+    #[derive(Debug)]
+    struct Words;
+
+    impl Segmenter for Words {
+        fn segment_indices<'text>(
+            &self,
+            text: &'text str,
+        ) -> SegmentIndices<'text> {
+            Segmentation::Words.segment_indices(text)
+        }
+    }
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Words);
+    let source = builder.finish("test.txt", "hello world");
+    let json = serde_json::to_string(&source).unwrap();
+    assert!(serde_json::from_str::<Source>(&json).is_err());
+
+    let seed = SourceSeed::new(&builder);
+    let deserialized =
+        seed.deserialize(&mut Deserializer::from_str(&json)).unwrap();
+    assert_eq!(deserialized.segmentation(), None);
+    assert_eq!(deserialized.len(), 3);
+    assert_eq!(&deserialized[2], "world");
+}
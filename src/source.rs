@@ -1,25 +1,50 @@
 //! Exports source code object and related items.
 
+mod buffer;
+mod builder;
+mod edit;
 mod indexing;
+mod load;
+mod map;
+mod normalize;
+mod segmenter;
+mod table;
 
 #[cfg(test)]
 mod test;
 
-use crate::{location::Location, span::Span};
+use crate::{
+    location::{advance_visual_column, Location},
+    span::Span,
+    stream::StreamId,
+};
+pub use builder::{CaseFolding, LineBreakPolicy, SourceBuilder};
+pub use edit::{EditError, PositionMap, TextEdit};
 pub use indexing::SourceIndex;
-use indexing::{IndexArray, IndexArrayBuilder, IndexArrayIter};
+use indexing::{IndexArray, IndexArrayIter};
+use load::Contents;
+pub use load::{InvalidUtf8Error, LoadError, Utf8Policy};
+pub use map::{FileId, FileLocation, FileSpan, SourceMap, Sources};
+use normalize::Normalization;
+pub use normalize::NormalizationForm;
+pub use segmenter::{SegmentIndices, Segmentation, Segmenter};
 use std::{
     cmp::Ordering,
+    error::Error,
     fmt,
     hash::{Hash, Hasher},
+    io::Read,
     ops::Index,
+    path::{Path, PathBuf},
     sync::Arc,
 };
-use unicode_segmentation::UnicodeSegmentation;
+use table::SegmentTable;
 
+/// Counts the segments of the given text in the default segmentation, i.e.
+/// extended grapheme clusters.
 #[doc(hidden)]
 pub fn count_grapheme_clusters(input: &str) -> usize {
-    input.graphemes(true).count()
+    Segmentation::default().count(input)
 }
 
 /// Inner structure of a source.
@@ -27,12 +52,26 @@ pub fn count_grapheme_clusters(input: &str) -> usize {
 struct SourceInner {
     /// File name.
     name: Box<str>,
+    /// Path the source was loaded from, if any.
+    path: Option<PathBuf>,
+    /// ID of this source in the source map that owns it, if any.
+    file_id: Option<FileId>,
     /// Contents of the source.
-    contents: Box<str>,
-    /// List of string segmentation in the source.
-    segments: IndexArray,
-    /// List of newlines in the source.
-    newlines: IndexArray,
+    contents: Contents,
+    /// Policy used to find line breaks.
+    line_break_policy: LineBreakPolicy,
+    /// Case folding used by case-insensitive comparisons.
+    case_folding: CaseFolding,
+    /// How contents are split into segments.
+    segmenter: Arc<dyn Segmenter>,
+    /// Table of string segmentation and newlines in the source.
+    table: SegmentTable,
+    /// Positions of segments where invalid UTF-8 was replaced.
+    invalid_utf8: IndexArray,
+    /// Original text and offset mapping, if the contents were normalized.
+    normalization: Option<Normalization>,
+    /// Identity of the stream this source is a snapshot of, if any.
+    stream_id: Option<StreamId>,
 }
 
 /// A source code object, such as read from a file. Cloning this object results
@@ -47,29 +86,66 @@ pub struct Source {
 impl Source {
     /// Creates a new source code object given its name and its contents.
     ///
-    /// Contents are rearranged as grapheme clusters.
+    /// Contents are rearranged as grapheme clusters. Use [`SourceBuilder`] to
+    /// configure how contents are indexed.
     pub fn new<S0, S1>(name: S0, contents: S1) -> Self
     where
         S0: Into<Box<str>>,
         S1: Into<Box<str>>,
     {
-        let name = name.into();
-        let contents = contents.into();
-        let mut segments = IndexArrayBuilder::new();
-        let mut newlines = IndexArrayBuilder::new();
-
-        for (idx, grapheme) in contents.grapheme_indices(true) {
-            if grapheme == "\n" {
-                newlines.push(segments.len());
-            }
-            segments.push(idx);
-        }
-        segments.push(contents.len());
+        SourceBuilder::new().finish(name, contents)
+    }
 
-        let segments = segments.into();
-        let newlines = newlines.into();
-        let inner = SourceInner { name, contents, segments, newlines };
-        Self { inner: Arc::new(inner) }
+    /// Creates a new source code object given its name and its contents,
+    /// normalized into the given form, so that e.g. NFC and NFD texts have the
+    /// same segments. Locations and spans can still report where they are in
+    /// the original text. See [`SourceBuilder::finish_normalized`].
+    pub fn new_normalized<S0, S1>(
+        name: S0,
+        contents: S1,
+        form: NormalizationForm,
+    ) -> Self
+    where
+        S0: Into<Box<str>>,
+        S1: Into<Box<str>>,
+    {
+        SourceBuilder::new().finish_normalized(name, contents, form)
+    }
+
+    /// Loads a source code object from the file at the given path, named after
+    /// the path. A leading UTF-8 byte order mark is stripped, and invalid
+    /// UTF-8 is an error. Use [`SourceBuilder::from_path`] to configure this.
+    pub fn from_path<P>(path: P) -> Result<Self, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        SourceBuilder::new().from_path(path)
+    }
+
+    /// Loads a source code object with the given name from the given reader.
+    /// A leading UTF-8 byte order mark is stripped, and invalid UTF-8 is an
+    /// error. Use [`SourceBuilder::from_reader`] to configure this.
+    pub fn from_reader<S, R>(name: S, reader: R) -> Result<Self, LoadError>
+    where
+        S: Into<Box<str>>,
+        R: Read,
+    {
+        SourceBuilder::new().from_reader(name, reader)
+    }
+
+    /// Memory-maps the file at the given path and creates a lazily indexed
+    /// source code object from it, named after the path. Invalid UTF-8 is an
+    /// error. Use [`SourceBuilder::map_path`] to configure this.
+    ///
+    /// # Safety
+    /// The file must not be modified while the source is alive. See
+    /// [`SourceBuilder::map_path`].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn map_path<P>(path: P) -> Result<Self, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        unsafe { SourceBuilder::new().map_path(path) }
     }
 
     /// The (file) name of the source.
@@ -77,32 +153,192 @@ impl Source {
         &self.inner.name
     }
 
+    /// The path this source was loaded from, or `None` if it was not loaded
+    /// from a file.
+    pub fn path(&self) -> Option<&Path> {
+        self.inner.path.as_deref()
+    }
+
+    /// Iterator over the locations of the segments where invalid UTF-8 was
+    /// replaced by `U+FFFD` when loading (see [`Utf8Policy::Lossy`]).
+    pub fn invalid_utf8(&self) -> InvalidUtf8Locations<'_> {
+        InvalidUtf8Locations {
+            source: self,
+            inner: self.inner.invalid_utf8.iter(),
+        }
+    }
+
+    /// The ID of this source in the [`SourceMap`] that owns it, or `None` if
+    /// it was created standalone.
+    pub fn file_id(&self) -> Option<FileId> {
+        self.inner.file_id
+    }
+
     /// The length the source.
     pub fn len(&self) -> usize {
-        self.inner.segments.len() - 1
+        self.inner.table.len(self.contents())
+    }
+
+    /// Tests whether the source has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
     }
 
     /// The contentss of the source.
     pub fn contents(&self) -> &str {
-        &self.inner.contents
+        self.inner.contents.as_str()
+    }
+
+    /// The policy used to find line breaks in this source.
+    pub fn line_break_policy(&self) -> LineBreakPolicy {
+        self.inner.line_break_policy
+    }
+
+    /// The case folding used when segments of this source are compared
+    /// case-insensitively.
+    pub fn case_folding(&self) -> CaseFolding {
+        self.inner.case_folding
+    }
+
+    /// How the contents of this source are split into segments.
+    pub fn segmenter(&self) -> &dyn Segmenter {
+        &*self.inner.segmenter
+    }
+
+    /// The built-in segmentation of this source, or `None` if it is split into
+    /// segments by a custom [`Segmenter`].
+    pub fn segmentation(&self) -> Option<Segmentation> {
+        self.inner.segmenter.segmentation()
+    }
+
+    /// The form the contents were normalized into, or `None` if they were not
+    /// normalized.
+    pub fn normalization_form(&self) -> Option<NormalizationForm> {
+        self.inner.normalization.as_ref().map(Normalization::form)
+    }
+
+    /// The contents of the source before normalization. The same as
+    /// [`Source::contents`] if they were not normalized.
+    pub fn original_contents(&self) -> &str {
+        self.original_source().contents()
+    }
+
+    /// A source over the contents before normalization, with the same name
+    /// and options, indexed lazily (see [`SourceBuilder::finish_lazy`]). This
+    /// source itself if the contents were not normalized.
+    pub fn original_source(&self) -> &Source {
+        match &self.inner.normalization {
+            Some(normalization) => normalization.original(),
+            None => self,
+        }
+    }
+
+    /// Maps a byte index of the contents to the original contents (see
+    /// [`Source::original_contents`]). An index inside the normalization of an
+    /// original grapheme cluster maps to the start of the cluster, or to its
+    /// end if `round_up` is set.
+    pub(super) fn original_byte_index(
+        &self,
+        byte_index: usize,
+        round_up: bool,
+    ) -> usize {
+        match &self.inner.normalization {
+            Some(normalization) => {
+                normalization.original_offset(byte_index, round_up)
+            },
+            None => byte_index,
+        }
     }
 
     /// Iterator over the segment indices of the source, where indices are in
     /// terms of bytes.
-    pub fn seg_byte_indices(&self) -> SegmentByteIndices {
-        SegmentByteIndices { inner: self.inner.segments.iter() }
+    pub fn seg_byte_indices(&self) -> SegmentByteIndices<'_> {
+        SegmentByteIndices { source: self, front: 0, back: self.len() + 1 }
     }
 
     /// Iterator over the newline indices of the source, where indices are in
     /// terms of segments/grapheme clusters.
-    pub fn newline_indices(&self) -> NewlineIndices {
-        NewlineIndices { inner: self.inner.segments.iter() }
+    pub fn newline_indices(&self) -> NewlineIndices<'_> {
+        NewlineIndices { source: self, front: 0, back: self.line_count() - 1 }
+    }
+
+    /// The number of lines in the source. It is always at least `1`, since
+    /// an empty source has a single empty line, and a source ending with a
+    /// line break has an empty last line.
+    pub fn line_count(&self) -> usize {
+        self.inner.table.newline_count(self.contents()) + 1
+    }
+
+    /// Returns a span of the given line (starting from `0`), including its
+    /// line break, or `None` if the line does not exist.
+    pub fn line(&self, line: usize) -> Option<Span> {
+        let start = self.try_line_start(line)?;
+        let end = self.try_line_start(line + 1).unwrap_or(self.len());
+        let location = Location::new_unchecked(self.clone(), start);
+        Some(Span::new_unchecked(location, end - start))
+    }
+
+    /// Returns a span of the given line (starting from `0`), excluding its
+    /// line break, or `None` if the line does not exist.
+    pub fn line_content(&self, line: usize) -> Option<Span> {
+        let start = self.try_line_start(line)?;
+        let end = self.try_line_end(line)?;
+        let location = Location::new_unchecked(self.clone(), start);
+        Some(Span::new_unchecked(location, end - start))
+    }
+
+    /// Iterator over the lines of the source as spans, including their line
+    /// breaks.
+    pub fn lines(&self) -> Lines {
+        Lines { source: self.clone(), front: 0, back: self.line_count() }
+    }
+
+    /// Returns the byte index where the segment at the given position starts.
+    ///
+    /// # Panics
+    /// Panics if the position is past beyond source length.
+    pub(super) fn byte_index(&self, position: usize) -> usize {
+        match self.try_byte_index(position) {
+            Some(byte_index) => byte_index,
+            None => {
+                panic!("Position {} is past beyond the source length", position)
+            },
+        }
+    }
+
+    /// Returns the byte index where the segment at the given position starts,
+    /// or `None` if the position is past beyond source length. Only indexes
+    /// lazily indexed sources up to the position.
+    pub(super) fn try_byte_index(&self, position: usize) -> Option<usize> {
+        self.inner.table.byte_index(self.contents(), position)
+    }
+
+    /// Returns the position of the nth line break, or `None` if there are
+    /// not as many line breaks.
+    pub(super) fn newline_position(&self, index: usize) -> Option<usize> {
+        self.inner.table.newline(self.contents(), index)
+    }
+
+    /// Searches the position of the segment starting at the given byte index,
+    /// like a binary search over every segment's start followed by the
+    /// contents' length.
+    pub(super) fn search_byte(
+        &self,
+        byte_index: usize,
+    ) -> Result<usize, usize> {
+        self.inner.table.search_byte(self.contents(), byte_index)
+    }
+
+    /// Number of chunks indexed so far, or `None` if eagerly indexed.
+    #[cfg(test)]
+    pub(super) fn indexed_chunks(&self) -> Option<usize> {
+        self.inner.table.indexed_chunks()
     }
 
     /// Returns the line number where the given position is contained, starting
     /// from `0`.
-    pub(super) fn line(&self, position: usize) -> usize {
-        match self.inner.newlines.binary_search(position) {
+    pub(super) fn line_of(&self, position: usize) -> usize {
+        match self.inner.table.search_newline(self.contents(), position) {
             Ok(n) | Err(n) => n,
         }
     }
@@ -116,7 +352,10 @@ impl Source {
         if line == 0 {
             0
         } else {
-            self.inner.newlines.index(line - 1) + 1
+            match self.newline_position(line - 1) {
+                Some(position) => position + 1,
+                None => panic!("Line {} does not exist", line),
+            }
         }
     }
 
@@ -126,7 +365,118 @@ impl Source {
         if line == 0 {
             Some(0)
         } else {
-            self.inner.newlines.get(line - 1).map(|position| position + 1)
+            self.newline_position(line - 1).map(|position| position + 1)
+        }
+    }
+
+    /// Finds the location at the given line and column, both starting from `0`
+    /// and counted in grapheme clusters/segments. The column right after the
+    /// last segment of a line (i.e. the line break) is valid.
+    ///
+    /// Returns an error if the line does not exist or if the column exceeds
+    /// the line's length.
+    pub fn location_at(
+        &self,
+        line: usize,
+        column: usize,
+    ) -> Result<Location, LineColumnError> {
+        let (start, end) =
+            match (self.try_line_start(line), self.try_line_end(line)) {
+                (Some(start), Some(end)) => (start, end),
+                _ => {
+                    return Err(LineColumnError::LineOutOfBounds {
+                        line,
+                        line_count: self.line_count(),
+                    })
+                },
+            };
+        if column > end - start {
+            return Err(LineColumnError::ColumnOutOfBounds {
+                line,
+                column,
+                line_len: end - start,
+            });
+        }
+        Ok(Location::new_unchecked(self.clone(), start + column))
+    }
+
+    /// Finds the location at the given line and column, both starting from `0`
+    /// and counted in grapheme clusters/segments. A line past beyond the last
+    /// one is clamped to the last line, and a column past beyond the line's
+    /// end is clamped to the line's end.
+    pub fn location_at_clamped(&self, line: usize, column: usize) -> Location {
+        let line = line.min(self.line_count() - 1);
+        let start = self.line_start(line);
+        let end = self.try_line_end(line).unwrap_or(self.len());
+        let position = start + column.min(end - start);
+        Location::new_unchecked(self.clone(), position)
+    }
+
+    /// Finds the location of the segment starting at the given byte offset.
+    /// The end of the source is also a valid byte offset.
+    ///
+    /// Returns an error if the byte offset falls inside a grapheme
+    /// cluster/segment or past beyond the source end.
+    pub fn location_at_byte(
+        &self,
+        byte_offset: usize,
+    ) -> Result<Location, NotOnBoundary> {
+        if byte_offset > self.contents().len() {
+            return Err(NotOnBoundary { byte_offset, containing: None });
+        }
+        match self.search_byte(byte_offset) {
+            Ok(position) => Ok(Location::new_unchecked(self.clone(), position)),
+            Err(position) => Err(NotOnBoundary {
+                byte_offset,
+                containing: Some(Location::new_unchecked(
+                    self.clone(),
+                    position - 1,
+                )),
+            }),
+        }
+    }
+
+    /// Returns the position where the given line's contents end, i.e. the
+    /// position of its line break or the source end for the last line.
+    /// Returns `None` on invalid line number.
+    pub(super) fn try_line_end(&self, line: usize) -> Option<usize> {
+        match self.newline_position(line) {
+            Some(position) => Some(position),
+            None if line + 1 == self.line_count() => Some(self.len()),
+            None => None,
+        }
+    }
+
+    /// Finds the location on the given line (starting from `0`) that is
+    /// displayed at the given visual column, with tabs expanded to the next
+    /// multiple of `tab_width` (see [`Location::visual_column`]). If the
+    /// visual column falls in the middle of a tab or a wide segment, that
+    /// segment's location is returned. The visual column right after the last
+    /// segment yields the location of the line break (or the source end).
+    ///
+    /// Returns `None` if the line does not exist or if the visual column is
+    /// past beyond the line's end.
+    pub fn location_at_visual_column(
+        &self,
+        line: usize,
+        visual_column: usize,
+        tab_width: usize,
+    ) -> Option<Location> {
+        let start = self.try_line_start(line)?;
+        let end = self.try_line_end(line)?;
+        let mut column = 0;
+        for position in start .. end {
+            let next =
+                advance_visual_column(column, &self[position], tab_width);
+            if visual_column < next {
+                return Some(Location::new_unchecked(self.clone(), position));
+            }
+            column = next;
+        }
+        if visual_column == column {
+            Some(Location::new_unchecked(self.clone(), end))
+        } else {
+            None
         }
     }
 
@@ -192,30 +542,133 @@ impl fmt::Display for Source {
     }
 }
 
+/// Error returned when a line and column pair does not exist in a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LineColumnError {
+    /// The line does not exist.
+    LineOutOfBounds {
+        /// The line given.
+        line: usize,
+        /// The number of lines in the source.
+        line_count: usize,
+    },
+    /// The column exceeds the line's length.
+    ColumnOutOfBounds {
+        /// The line given.
+        line: usize,
+        /// The column given.
+        column: usize,
+        /// The length of the line, excluding the line break.
+        line_len: usize,
+    },
+}
+
+impl fmt::Display for LineColumnError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::LineOutOfBounds { line, line_count } => write!(
+                fmtr,
+                "line {} does not exist, the source has {} lines",
+                line + 1,
+                line_count
+            ),
+            Self::ColumnOutOfBounds { line, column, line_len } => write!(
+                fmtr,
+                "column {} exceeds the length {} of line {}",
+                column + 1,
+                line_len,
+                line + 1
+            ),
+        }
+    }
+}
+
+impl Error for LineColumnError {}
+
+/// Error returned when a byte offset does not start a grapheme cluster/segment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NotOnBoundary {
+    /// The byte offset given.
+    byte_offset: usize,
+    /// The location of the segment containing the byte offset, if any.
+    containing: Option<Location>,
+}
+
+impl NotOnBoundary {
+    /// The byte offset that is not on a segment boundary.
+    pub fn byte_offset(&self) -> usize {
+        self.byte_offset
+    }
+
+    /// The location of the segment containing the byte offset, or `None` if
+    /// the offset is past beyond the source end.
+    pub fn containing(&self) -> Option<&Location> {
+        self.containing.as_ref()
+    }
+}
+
+impl fmt::Display for NotOnBoundary {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match &self.containing {
+            Some(location) => write!(
+                fmtr,
+                "byte offset {} is inside the segment {:?} {}",
+                self.byte_offset,
+                location.as_str(),
+                location
+            ),
+            None => {
+                write!(
+                    fmtr,
+                    "byte offset {} is out of bounds",
+                    self.byte_offset
+                )
+            },
+        }
+    }
+}
+
+impl Error for NotOnBoundary {}
+
 /// Iterator over the segment indices of a source. Indices are in terms of
 /// bytes. Double-ended and sized.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SegmentByteIndices<'src> {
-    /// The inner iterator over the indices.
-    inner: IndexArrayIter<'src>,
+    /// The source whose indices are iterated.
+    source: &'src Source,
+    /// Next index yielded from the front.
+    front: usize,
+    /// Index after the next index yielded from the back.
+    back: usize,
 }
 
 impl<'src> Iterator for SegmentByteIndices<'src> {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
+        if self.front < self.back {
+            let index = self.source.try_byte_index(self.front);
+            self.front += 1;
+            index
+        } else {
+            None
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.inner.len();
+        let len = self.back - self.front;
         (len, Some(len))
     }
 }
 
 impl<'src> DoubleEndedIterator for SegmentByteIndices<'src> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.inner.next_back()
+        if self.front < self.back {
+            self.back -= 1;
+            self.source.try_byte_index(self.back)
+        } else {
+            None
+        }
     }
 }
 
@@ -223,29 +676,121 @@ impl<'array> ExactSizeIterator for SegmentByteIndices<'array> {}
 
 /// Iterator over the newline indices of a source. Indices are in term of
 /// segments, not bytes nor characters. Double-ended and sized.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NewlineIndices<'src> {
-    /// The inner iterator over the indices.
-    inner: IndexArrayIter<'src>,
+    /// The source whose indices are iterated.
+    source: &'src Source,
+    /// Next index yielded from the front.
+    front: usize,
+    /// Index after the next index yielded from the back.
+    back: usize,
 }
 
 impl<'src> Iterator for NewlineIndices<'src> {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next()
+        if self.front < self.back {
+            let index = self.source.newline_position(self.front);
+            self.front += 1;
+            index
+        } else {
+            None
+        }
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.inner.len();
+        let len = self.back - self.front;
         (len, Some(len))
     }
 }
 
 impl<'src> DoubleEndedIterator for NewlineIndices<'src> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        self.inner.next_back()
+        if self.front < self.back {
+            self.back -= 1;
+            self.source.newline_position(self.back)
+        } else {
+            None
+        }
     }
 }
 
 impl<'array> ExactSizeIterator for NewlineIndices<'array> {}
+
+/// Iterator over the locations where invalid UTF-8 was replaced in a source.
+/// Created by [`Source::invalid_utf8`]. Double-ended and sized.
+#[derive(Debug)]
+pub struct InvalidUtf8Locations<'src> {
+    /// The source of the locations.
+    source: &'src Source,
+    /// The inner iterator over the positions.
+    inner: IndexArrayIter<'src>,
+}
+
+impl<'src> Iterator for InvalidUtf8Locations<'src> {
+    type Item = Location;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let position = self.inner.next()?;
+        Some(Location::new_unchecked(self.source.clone(), position))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.inner.len();
+        (len, Some(len))
+    }
+}
+
+impl<'src> DoubleEndedIterator for InvalidUtf8Locations<'src> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let position = self.inner.next_back()?;
+        Some(Location::new_unchecked(self.source.clone(), position))
+    }
+}
+
+impl<'src> ExactSizeIterator for InvalidUtf8Locations<'src> {}
+
+/// Iterator over the lines of a source as spans, including their line breaks.
+/// Created by [`Source::lines`]. Double-ended and sized.
+#[derive(Debug, Clone)]
+pub struct Lines {
+    /// The source whose lines are iterated.
+    source: Source,
+    /// Next line yielded from the front.
+    front: usize,
+    /// Line after the next line yielded from the back.
+    back: usize,
+}
+
+impl Iterator for Lines {
+    type Item = Span;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            let span = self.source.line(self.front);
+            self.front += 1;
+            span
+        } else {
+            None
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for Lines {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front < self.back {
+            self.back -= 1;
+            self.source.line(self.back)
+        } else {
+            None
+        }
+    }
+}
+
+impl ExactSizeIterator for Lines {}
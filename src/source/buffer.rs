@@ -0,0 +1,138 @@
+//! Exports the append-only buffer shared by the snapshots of a stream.
+
+use std::{
+    cell::UnsafeCell,
+    fmt,
+    ptr,
+    slice,
+    str,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// Minimum capacity of a buffer, in bytes.
+const MIN_CAPACITY: usize = 64;
+
+/// Bytes of appended contents, each written at most once.
+struct Buffer {
+    /// The bytes, of which only the reserved prefix is written.
+    bytes: Box<[UnsafeCell<u8>]>,
+    /// Number of bytes reserved by appends. Bytes past it are neither read
+    /// nor written.
+    reserved: AtomicUsize,
+}
+
+// SAFETY: a byte is only written by the append that reserved it, which no
+// other append can reserve again, and it is only read through contents created
+// after it was written.
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    /// Creates a buffer of the given capacity, with the given number of
+    /// bytes reserved.
+    fn new(capacity: usize, reserved: usize) -> Self {
+        Self {
+            bytes: (0 .. capacity).map(|_| UnsafeCell::new(0)).collect(),
+            reserved: AtomicUsize::new(reserved),
+        }
+    }
+
+    /// Pointer to the byte at the given index.
+    fn byte_ptr(&self, index: usize) -> *mut u8 {
+        UnsafeCell::raw_get(self.bytes[index ..].as_ptr())
+    }
+
+    /// Writes the given text at the given byte index.
+    ///
+    /// # Safety
+    /// The bytes written must have been reserved by the caller, and must not
+    /// have been written before.
+    unsafe fn write(&self, index: usize, text: &str) {
+        // SAFETY: no one else reads or writes these bytes, as the caller
+        // reserved them.
+        unsafe {
+            ptr::copy_nonoverlapping(
+                text.as_ptr(),
+                self.byte_ptr(index),
+                text.len(),
+            )
+        }
+    }
+}
+
+/// Contents of a snapshot of a stream: a prefix of a buffer shared with the
+/// other snapshots. Text appended to the last snapshot is written in place,
+/// after the prefix, instead of copying the whole contents.
+#[derive(Clone)]
+pub(super) struct AppendedContents {
+    /// The shared buffer.
+    buffer: Arc<Buffer>,
+    /// Length of the contents. The bytes up to it are written, are valid
+    /// UTF-8, and are never written again.
+    len: usize,
+}
+
+impl AppendedContents {
+    /// Creates contents made of the given prefix followed by the given text,
+    /// in a new buffer with room to append as much text again.
+    pub(super) fn new(prefix: &str, text: &str) -> Self {
+        let len = prefix.len() + text.len();
+        let buffer = Buffer::new((len * 2).max(MIN_CAPACITY), len);
+        // SAFETY: the buffer is not shared yet, and the bytes were reserved
+        // on creation.
+        unsafe {
+            buffer.write(0, prefix);
+            buffer.write(prefix.len(), text);
+        }
+        Self { buffer: Arc::new(buffer), len }
+    }
+
+    /// The contents as a string.
+    pub(super) fn as_str(&self) -> &str {
+        // SAFETY: the bytes up to the length are written, are valid UTF-8,
+        // and are never written again.
+        unsafe {
+            str::from_utf8_unchecked(slice::from_raw_parts(
+                self.buffer.byte_ptr(0),
+                self.len,
+            ))
+        }
+    }
+
+    /// Creates contents with the given text appended to these. The text is
+    /// written in place if no text was appended to these contents yet and
+    /// the buffer has room for it. Otherwise, the contents are copied into a
+    /// new buffer twice as large, so appending costs amortized constant time
+    /// per byte, as long as text is appended to the last contents.
+    pub(super) fn append(&self, text: &str) -> Self {
+        let len = self.len + text.len();
+        let reserved = len <= self.buffer.bytes.len()
+            && self
+                .buffer
+                .reserved
+                .compare_exchange(
+                    self.len,
+                    len,
+                    Ordering::AcqRel,
+                    Ordering::Relaxed,
+                )
+                .is_ok();
+        if reserved {
+            // SAFETY: the bytes from the length of these contents up to the
+            // new length were just reserved. The reserved length only grows,
+            // so no other append can reserve them.
+            unsafe { self.buffer.write(self.len, text) };
+            Self { buffer: self.buffer.clone(), len }
+        } else {
+            Self::new(self.as_str(), text)
+        }
+    }
+}
+
+impl fmt::Debug for AppendedContents {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), fmtr)
+    }
+}
@@ -0,0 +1,525 @@
+//! Exports the builder of source code objects and its options.
+
+#[cfg(feature = "mmap")]
+use super::load::bom_len;
+use super::{
+    load::{decode_lossy, read_bytes},
+    Contents,
+    FileId,
+    InvalidUtf8Error,
+    LoadError,
+    Normalization,
+    NormalizationForm,
+    SegmentTable,
+    Segmentation,
+    Segmenter,
+    Source,
+    SourceInner,
+    Utf8Policy,
+};
+use crate::source::indexing::{IndexArray, IndexArrayBuilder};
+use std::{
+    fs::File,
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+/// Which segments are considered line breaks when indexing lines of a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LineBreakPolicy {
+    /// Only linefeed (`"\n"`) ends a line. Since `"\r\n"` is a single grapheme
+    /// cluster, it also ends a line.
+    LineFeed,
+    /// Linefeed (`"\n"`), carriage return followed by linefeed (`"\r\n"`) and
+    /// lone carriage return (`"\r"`) end a line.
+    Ascii,
+    /// Every Unicode line terminator ends a line: the ones in
+    /// [`LineBreakPolicy::Ascii`], next line (`U+0085`), line separator
+    /// (`U+2028`) and paragraph separator (`U+2029`).
+    #[default]
+    Unicode,
+}
+
+impl LineBreakPolicy {
+    /// Tests whether the given segment is a line break under this policy.
+    pub fn is_line_break(self, segment: &str) -> bool {
+        match self {
+            Self::LineFeed => matches!(segment, "\n" | "\r\n"),
+            Self::Ascii => matches!(segment, "\n" | "\r\n" | "\r"),
+            Self::Unicode => matches!(
+                segment,
+                "\n" | "\r\n" | "\r" | "\u{85}" | "\u{2028}" | "\u{2029}"
+            ),
+        }
+    }
+
+    /// Tests whether the given segment, followed by the given text, ends a
+    /// line under this policy, i.e. whether the segment ends with a line
+    /// break, such as a sentence ending with a linefeed. A carriage return
+    /// directly followed by a linefeed does not, since the line ends at the
+    /// linefeed. This only happens when segmenting by
+    /// [`Segmentation::Chars`].
+    pub(crate) fn ends_line(self, segment: &str, following: &str) -> bool {
+        let terminator = if segment.ends_with("\r\n") {
+            "\r\n"
+        } else if let Some((index, _)) = segment.char_indices().next_back() {
+            &segment[index ..]
+        } else {
+            return false;
+        };
+        self.is_line_break(terminator)
+            && !(terminator == "\r" && following.starts_with('\n'))
+    }
+}
+
+/// How segments are case folded when compared case-insensitively, e.g. by
+/// nom's `tag_no_case`.
+///
+/// Case-insensitive comparisons are always grapheme-aligned: segments are
+/// folded and compared one by one, so a segment only ever matches a single
+/// segment, even when folding changes the number of code points. For instance,
+/// `"ß"` matches `"ẞ"` (both fold to `"ss"`), but not the two segments `"SS"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaseFolding {
+    /// Unicode default full case folding, e.g. `"ß"` folds to `"ss"` and final
+    /// sigma (`"ς"`) folds to `"σ"`.
+    #[default]
+    Default,
+    /// Default folding with the Turkic mappings for dotted and dotless i:
+    /// `"I"` folds to `"ı"` and `"İ"` folds to `"i"`.
+    Turkic,
+}
+
+impl CaseFolding {
+    /// Folds the case of the given string.
+    pub fn fold(self, input: &str) -> String {
+        input.chars().flat_map(|ch| self.fold_char(ch)).collect()
+    }
+
+    /// Tests whether the given strings are equal after folding their case,
+    /// without allocating.
+    pub fn eq_ignore_case(self, left: &str, right: &str) -> bool {
+        left.chars()
+            .flat_map(|ch| self.fold_char(ch))
+            .eq(right.chars().flat_map(|ch| self.fold_char(ch)))
+    }
+
+    /// Folds the case of a single character. Full case folding is computed by
+    /// lowercasing the uppercase of the lowercase, which maps every character
+    /// to the same representative as its fold, e.g. `"ẞ"` to `"ß"` to `"SS"`
+    /// to `"ss"`.
+    fn fold_char(self, ch: char) -> impl Iterator<Item = char> {
+        let special = match (self, ch) {
+            (Self::Turkic, 'I' | 'ı') => Some('ı'),
+            (Self::Turkic, 'İ' | 'i') => Some('i'),
+            // Dotless i has no default folding, and uppercases to `'I'`.
+            (Self::Default, 'ı') => Some('ı'),
+            _ => None,
+        };
+        let folded = match special {
+            Some(_) => None,
+            None => Some(
+                ch.to_lowercase()
+                    .flat_map(char::to_uppercase)
+                    .flat_map(char::to_lowercase),
+            ),
+        };
+        special.into_iter().chain(folded.into_iter().flatten())
+    }
+}
+
+/// Default size in bytes of the chunks of lazily indexed sources.
+const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+
+/// Builder of a [`Source`], allowing to configure how the contents are
+/// indexed. A single builder can create many sources with the same options.
+#[derive(Debug, Clone)]
+pub struct SourceBuilder {
+    /// Policy used to find line breaks.
+    line_break_policy: LineBreakPolicy,
+    /// Policy used to handle invalid UTF-8 when loading sources.
+    utf8_policy: Utf8Policy,
+    /// Case folding used by case-insensitive comparisons.
+    case_folding: CaseFolding,
+    /// How contents are split into segments.
+    segmenter: Arc<dyn Segmenter>,
+    /// Size in bytes of the chunks of lazily indexed sources.
+    chunk_size: usize,
+}
+
+impl Default for SourceBuilder {
+    fn default() -> Self {
+        Self {
+            line_break_policy: LineBreakPolicy::default(),
+            utf8_policy: Utf8Policy::default(),
+            case_folding: CaseFolding::default(),
+            segmenter: Arc::new(Segmentation::default()),
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+/// Builders are equal when their options are. Built-in segmentations are
+/// compared by value, custom segmenters by identity, i.e. only clones of a
+/// builder share an equal custom segmenter.
+impl PartialEq for SourceBuilder {
+    fn eq(&self, other: &Self) -> bool {
+        let same_segmenter = match (self.segmentation(), other.segmentation()) {
+            (Some(left), Some(right)) => left == right,
+            _ => Arc::ptr_eq(&self.segmenter, &other.segmenter),
+        };
+        self.line_break_policy == other.line_break_policy
+            && self.utf8_policy == other.utf8_policy
+            && self.case_folding == other.case_folding
+            && same_segmenter
+            && self.chunk_size == other.chunk_size
+    }
+}
+
+impl Eq for SourceBuilder {}
+
+impl SourceBuilder {
+    /// Creates a new builder with default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the policy used to find line breaks. Defaults to
+    /// [`LineBreakPolicy::Unicode`].
+    pub fn line_break_policy(&mut self, policy: LineBreakPolicy) -> &mut Self {
+        self.line_break_policy = policy;
+        self
+    }
+
+    /// Sets the policy used to handle invalid UTF-8 when loading sources from
+    /// paths or readers. Defaults to [`Utf8Policy::Strict`].
+    pub fn utf8_policy(&mut self, policy: Utf8Policy) -> &mut Self {
+        self.utf8_policy = policy;
+        self
+    }
+
+    /// Sets the case folding used when segments of the sources are compared
+    /// case-insensitively. Defaults to [`CaseFolding::Default`].
+    pub fn case_folding(&mut self, folding: CaseFolding) -> &mut Self {
+        self.case_folding = folding;
+        self
+    }
+
+    /// Sets how the contents of the sources are split into segments, either
+    /// one of the built-in [`Segmentation`]s or a custom [`Segmenter`].
+    /// Defaults to [`Segmentation::Extended`].
+    pub fn segmenter<S>(&mut self, segmenter: S) -> &mut Self
+    where
+        S: Segmenter + 'static,
+    {
+        self.segmenter = Arc::new(segmenter);
+        self
+    }
+
+    /// The built-in segmentation set in this builder, or `None` if a custom
+    /// [`Segmenter`] is set.
+    pub fn segmentation(&self) -> Option<Segmentation> {
+        self.segmenter.segmentation()
+    }
+
+    /// Sets the size in bytes of the chunks in which lazily indexed sources
+    /// are segmented on demand. Defaults to 1 MiB.
+    ///
+    /// # Panics
+    /// Panics if the size is zero.
+    pub fn chunk_size(&mut self, bytes: usize) -> &mut Self {
+        if bytes == 0 {
+            panic!("Chunk size must not be zero");
+        }
+        self.chunk_size = bytes;
+        self
+    }
+
+    /// Creates a new source code object given its name and its contents,
+    /// using the options of this builder.
+    ///
+    /// Contents are split into segments by the segmenter of this builder.
+    pub fn finish<S0, S1>(&self, name: S0, contents: S1) -> Source
+    where
+        S0: Into<Box<str>>,
+        S1: Into<Box<str>>,
+    {
+        self.create(name.into(), None, contents.into(), &[], None)
+    }
+
+    /// Creates a new source code object given its name and its contents,
+    /// normalized into the given form, using the options of this builder.
+    ///
+    /// Each extended grapheme cluster of the contents is normalized on its
+    /// own, whatever the segmenter of this builder is, which then splits the
+    /// normalized contents into segments. The original contents are kept, so
+    /// that locations and spans can report where they are in the original
+    /// text, e.g. with
+    /// [`Location::original_byte_offset`](crate::Location::original_byte_offset).
+    pub fn finish_normalized<S0, S1>(
+        &self,
+        name: S0,
+        contents: S1,
+        form: NormalizationForm,
+    ) -> Source
+    where
+        S0: Into<Box<str>>,
+        S1: Into<Box<str>>,
+    {
+        let original = self.create_lazy(
+            name.into(),
+            None,
+            Contents::Owned(contents.into()),
+        );
+        let name = original.name().into();
+        let (contents, normalization) = Normalization::new(original, form);
+        let mut inner = self.create_inner(name, None, contents, &[], None);
+        inner.normalization = Some(normalization);
+        Source { inner: Arc::new(inner) }
+    }
+
+    /// Creates a new source code object given its name and its contents,
+    /// using the options of this builder. Contents are segmented lazily, in
+    /// chunks (see [`SourceBuilder::chunk_size`]), as positions, byte offsets
+    /// and lines are accessed, so the head of large contents can be used
+    /// without segmenting all of it. Anything requiring the length of the
+    /// source, such as [`Source::full_span`], segments all of it.
+    pub fn finish_lazy<S0, S1>(&self, name: S0, contents: S1) -> Source
+    where
+        S0: Into<Box<str>>,
+        S1: Into<Box<str>>,
+    {
+        self.create_lazy(name.into(), None, Contents::Owned(contents.into()))
+    }
+
+    /// Memory-maps the file at the given path and creates a lazily indexed
+    /// source code object from it (see [`SourceBuilder::finish_lazy`]), using
+    /// the options of this builder. The source is named after the path.
+    ///
+    /// A leading UTF-8 byte order mark is skipped. The contents are
+    /// validated as UTF-8 upfront; if invalid, the file is read and handled
+    /// according to the [`Utf8Policy`], just like [`SourceBuilder::from_path`].
+    ///
+    /// # Safety
+    /// The file must not be modified, truncated or otherwise changed while
+    /// the source (or any location or span of it) is alive, as the mapped
+    /// contents would change or become inaccessible. See [`memmap2::Mmap`].
+    #[cfg(feature = "mmap")]
+    pub unsafe fn map_path<P>(&self, path: P) -> Result<Source, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let file = File::open(path)?;
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        let start = bom_len(&map);
+        if std::str::from_utf8(&map[start ..]).is_err() {
+            let name = path.display().to_string().into();
+            return self.load(
+                name,
+                Some(path.to_path_buf()),
+                map.to_vec(),
+                None,
+            );
+        }
+        let name = path.display().to_string().into();
+        let contents = Contents::Mapped { map, start };
+        Ok(self.create_lazy(name, Some(path.to_path_buf()), contents))
+    }
+
+    /// Loads a source code object from the file at the given path, using the
+    /// options of this builder. The source is named after the path, which is
+    /// also recorded (see [`Source::path`]).
+    ///
+    /// A leading UTF-8 byte order mark is stripped. Invalid UTF-8 is handled
+    /// according to the [`Utf8Policy`].
+    pub fn from_path<P>(&self, path: P) -> Result<Source, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        self.load_path(path.as_ref(), None)
+    }
+
+    /// Loads a source code object with the given name from the given reader,
+    /// using the options of this builder.
+    ///
+    /// A leading UTF-8 byte order mark is stripped. Invalid UTF-8 is handled
+    /// according to the [`Utf8Policy`].
+    pub fn from_reader<S, R>(
+        &self,
+        name: S,
+        reader: R,
+    ) -> Result<Source, LoadError>
+    where
+        S: Into<Box<str>>,
+        R: Read,
+    {
+        let bytes = read_bytes(reader)?;
+        self.load(name.into(), None, bytes, None)
+    }
+
+    /// Loads a source code object from the file at the given path, possibly
+    /// owned by a source map.
+    pub(super) fn load_path(
+        &self,
+        path: &Path,
+        file_id: Option<FileId>,
+    ) -> Result<Source, LoadError> {
+        let bytes = read_bytes(File::open(path)?)?;
+        let name = path.display().to_string().into();
+        self.load(name, Some(path.to_path_buf()), bytes, file_id)
+    }
+
+    /// Decodes loaded bytes and creates the source code object. On a strict
+    /// UTF-8 policy, invalid bytes yield an error holding a lossily decoded
+    /// source, which is never owned by a source map.
+    fn load(
+        &self,
+        name: Box<str>,
+        path: Option<PathBuf>,
+        bytes: Vec<u8>,
+        file_id: Option<FileId>,
+    ) -> Result<Source, LoadError> {
+        let (contents, invalid_utf8) = decode_lossy(&bytes);
+        if self.utf8_policy == Utf8Policy::Strict && !invalid_utf8.is_empty() {
+            let source = self.create(name, path, contents, &invalid_utf8, None);
+            return Err(LoadError::InvalidUtf8(InvalidUtf8Error::new(source)));
+        }
+        Ok(self.create(name, path, contents, &invalid_utf8, file_id))
+    }
+
+    /// Creates a lazily indexed source code object.
+    fn create_lazy(
+        &self,
+        name: Box<str>,
+        path: Option<PathBuf>,
+        contents: Contents,
+    ) -> Source {
+        let table = SegmentTable::lazy(
+            contents.as_str().len(),
+            self.chunk_size,
+            self.segmenter.clone(),
+            self.line_break_policy,
+        );
+        let inner = SourceInner {
+            name,
+            path,
+            file_id: None,
+            contents,
+            line_break_policy: self.line_break_policy,
+            case_folding: self.case_folding,
+            segmenter: self.segmenter.clone(),
+            table,
+            invalid_utf8: IndexArrayBuilder::new().into(),
+            normalization: None,
+            stream_id: None,
+        };
+        Source { inner: Arc::new(inner) }
+    }
+
+    /// Segments the contents and creates the source code object, possibly
+    /// loaded from a path and owned by a source map. `invalid_utf8` holds the
+    /// byte indices where invalid UTF-8 was replaced, in ascending order.
+    pub(super) fn create(
+        &self,
+        name: Box<str>,
+        path: Option<PathBuf>,
+        contents: Box<str>,
+        invalid_utf8: &[usize],
+        file_id: Option<FileId>,
+    ) -> Source {
+        let inner =
+            self.create_inner(name, path, contents, invalid_utf8, file_id);
+        Source { inner: Arc::new(inner) }
+    }
+
+    /// Segments the contents and creates the inner structure of a source code
+    /// object. See [`SourceBuilder::create`].
+    fn create_inner(
+        &self,
+        name: Box<str>,
+        path: Option<PathBuf>,
+        contents: Box<str>,
+        invalid_utf8: &[usize],
+        file_id: Option<FileId>,
+    ) -> SourceInner {
+        let mut segments = IndexArrayBuilder::new();
+        let mut newlines = IndexArrayBuilder::new();
+        segment_into(
+            &contents, 0, &*self.segmenter, self.line_break_policy,
+            &mut segments, &mut newlines,
+        );
+        segments.push(contents.len());
+
+        let segments = segments.finish();
+        let invalid_utf8 = containing_positions(
+            |byte_index| segments.binary_search(byte_index),
+            invalid_utf8,
+        );
+        SourceInner {
+            name,
+            path,
+            file_id,
+            contents: Contents::Owned(contents),
+            line_break_policy: self.line_break_policy,
+            case_folding: self.case_folding,
+            segmenter: self.segmenter.clone(),
+            table: SegmentTable::Eager {
+                segments: Arc::new(segments),
+                newlines: Arc::new(newlines.finish()),
+            },
+            invalid_utf8,
+            normalization: None,
+            stream_id: None,
+        }
+    }
+}
+
+/// Segments the given text, pushing the byte index of each segment (shifted
+/// by `byte_offset`) to `segments` and the position of each line break to
+/// `newlines`. Positions continue from the current length of `segments`.
+pub(super) fn segment_into(
+    text: &str,
+    byte_offset: usize,
+    segmenter: &dyn Segmenter,
+    line_break_policy: LineBreakPolicy,
+    segments: &mut IndexArrayBuilder,
+    newlines: &mut IndexArrayBuilder,
+) {
+    for (idx, grapheme) in segmenter.segment_indices(text) {
+        let following = &text[idx + grapheme.len() ..];
+        if line_break_policy.ends_line(grapheme, following) {
+            newlines.push(segments.len());
+        }
+        segments.push(byte_offset + idx);
+    }
+}
+
+/// Finds the positions of the segments containing the given byte indices,
+/// which must be in ascending order, given a binary search over the byte
+/// indices of the segments. Byte indices in the same segment yield a single
+/// position.
+pub(super) fn containing_positions<F>(
+    search_byte: F,
+    byte_indices: &[usize],
+) -> IndexArray
+where
+    F: Fn(usize) -> Result<usize, usize>,
+{
+    let mut positions = IndexArrayBuilder::new();
+    let mut last = None;
+    for &byte_index in byte_indices {
+        let position = match search_byte(byte_index) {
+            Ok(position) => position,
+            Err(position) => position - 1,
+        };
+        if last != Some(position) {
+            positions.push(position);
+            last = Some(position);
+        }
+    }
+    positions.into()
+}
@@ -0,0 +1,587 @@
+//! Exports incremental edition of source code objects.
+
+#[cfg(test)]
+mod test;
+
+use super::{
+    buffer::AppendedContents,
+    builder::{containing_positions, segment_into},
+    Contents,
+    SegmentTable,
+    Segmenter,
+    Source,
+    SourceInner,
+};
+use crate::{
+    location::Location,
+    source::indexing::{IndexArrayBuilder, SplicedIndexArrayBuilder},
+    span::Span,
+    stream::StreamId,
+};
+use std::{error::Error, fmt, ops::Range, sync::Arc};
+
+/// A replacement of a range of segments in a source code object by some text.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextEdit {
+    /// Range of positions replaced, in terms of grapheme clusters/segments.
+    range: Range<usize>,
+    /// Replacement text.
+    text: Box<str>,
+}
+
+impl TextEdit {
+    /// Creates an edit replacing the given range of positions (in terms of
+    /// grapheme clusters/segments) by the given text. An empty range inserts
+    /// text, and an empty text deletes the range.
+    pub fn new<S>(range: Range<usize>, text: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self { range, text: text.into() }
+    }
+
+    /// Creates an edit replacing the positions covered by the given span by
+    /// the given text.
+    pub fn from_span<S>(span: &Span, text: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        let start = span.start().position();
+        Self::new(start .. start + span.len(), text)
+    }
+
+    /// The range of replaced positions, in terms of grapheme
+    /// clusters/segments.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The replacement text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Error returned when edits cannot be applied to a source code object.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EditError {
+    /// The range of an edit is reversed or past beyond the source end.
+    InvalidRange {
+        /// The range of the edit.
+        range: Range<usize>,
+        /// The length of the source.
+        len: usize,
+    },
+    /// Two edits replace overlapping ranges.
+    Overlapping {
+        /// The range of the first edit.
+        first: Range<usize>,
+        /// The range of the second edit.
+        second: Range<usize>,
+    },
+    /// The source was normalized, and edits cannot keep the mapping of the
+    /// normalized contents to the original ones.
+    Normalized,
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidRange { range, len } => write!(
+                fmtr,
+                "edit range {:?} is not valid in a source of length {}",
+                range, len
+            ),
+            Self::Overlapping { first, second } => {
+                write!(fmtr, "edit ranges {:?} and {:?} overlap", first, second)
+            },
+            Self::Normalized => {
+                write!(fmtr, "normalized sources cannot be edited")
+            },
+        }
+    }
+}
+
+impl Error for EditError {}
+
+/// An edit translated to bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ByteEdit {
+    /// Range of replaced bytes in the old source.
+    old: Range<usize>,
+    /// Length in bytes of the replacement text.
+    new_len: usize,
+}
+
+/// Mapping of positions of a source code object before some edits to
+/// positions of the source code object created by the edits. Created by
+/// [`Source::apply_edits`].
+#[derive(Debug, Clone)]
+pub struct PositionMap {
+    /// Source before the edits.
+    old: Source,
+    /// Source after the edits.
+    new: Source,
+    /// Edits applied, sorted.
+    edits: Box<[ByteEdit]>,
+}
+
+impl PositionMap {
+    /// The source code object before the edits.
+    pub fn old_source(&self) -> &Source {
+        &self.old
+    }
+
+    /// The source code object after the edits.
+    pub fn new_source(&self) -> &Source {
+        &self.new
+    }
+
+    /// Translates a position in the old source to the new source. Returns
+    /// `None` if the position was invalidated: it was inside a replaced
+    /// range, or its segment merged with an adjacent one. A position at the
+    /// start of a replaced range maps to the start of the replacement text,
+    /// and a position at the end maps to the end of the replacement text.
+    pub fn map_position(&self, position: usize) -> Option<usize> {
+        let old_byte = self.old.try_byte_index(position)?;
+        let new_byte = shift_byte(&self.edits, old_byte)?;
+        self.new.search_byte(new_byte).ok()
+    }
+
+    /// Translates a location in the old source to the new source. Returns
+    /// `None` if the location is not from the old source or if it was
+    /// invalidated (see [`PositionMap::map_position`]).
+    pub fn map_location(&self, location: &Location) -> Option<Location> {
+        if *location.source() != self.old {
+            return None;
+        }
+        let position = self.map_position(location.position())?;
+        Some(Location::new_unchecked(self.new.clone(), position))
+    }
+
+    /// Translates a span in the old source to the new source. Returns `None`
+    /// if the span is not from the old source or if either its start or its
+    /// end was invalidated (see [`PositionMap::map_position`]).
+    pub fn map_span(&self, span: &Span) -> Option<Span> {
+        let start = self.map_location(&span.start())?;
+        let end = self.map_location(&span.end())?;
+        Some(Span::from_range(start, end))
+    }
+}
+
+/// Translates a byte index in the old source to the new source, given the
+/// sorted byte edits. Returns `None` if the byte was strictly inside a
+/// replaced range.
+fn shift_byte(edits: &[ByteEdit], old_byte: usize) -> Option<usize> {
+    let mut new_byte = old_byte;
+    for edit in edits {
+        if old_byte <= edit.old.start {
+            break;
+        }
+        if old_byte < edit.old.end {
+            return None;
+        }
+        new_byte = new_byte + edit.new_len - edit.old.len();
+    }
+    Some(new_byte)
+}
+
+/// A range of old positions to be segmented again, together with the edits
+/// inside of it.
+#[derive(Debug, Clone)]
+struct Window {
+    /// Range of positions in the old source.
+    range: Range<usize>,
+    /// Range of edit indices.
+    edits: Range<usize>,
+}
+
+/// Tests whether segmenting the given text yields a segment boundary at the
+/// given byte index. A carriage return followed by a linefeed never has a
+/// boundary in between, even when segmenting by characters, since whether the
+/// carriage return ends a line depends on the linefeed. Segmentation stops at
+/// the first segment starting at or past the index.
+fn has_boundary(text: &str, index: usize, segmenter: &dyn Segmenter) -> bool {
+    let (before, after) = text.split_at(index);
+    if before.ends_with('\r') && after.starts_with('\n') {
+        return false;
+    }
+    index == text.len()
+        || segmenter
+            .segment_indices(text)
+            .map(|(start, _)| start)
+            .find(|&start| start >= index)
+            == Some(index)
+}
+
+/// Finds a segment boundary of the given text that appending text cannot
+/// move, given a boundary `from` that appending cannot move either: the start
+/// of the first unstable segment (see [`Segmenter::unstable_segments`]). Only
+/// the text from `from` is segmented.
+fn stable_boundary(
+    text: &str,
+    from: usize,
+    segmenter: &dyn Segmenter,
+) -> usize {
+    let starts: Vec<_> = segmenter
+        .segment_indices(&text[from ..])
+        .map(|(start, _)| start)
+        .collect();
+    let unstable = segmenter.unstable_segments().min(starts.len());
+    match starts.len().checked_sub(unstable).and_then(|i| starts.get(i)) {
+        Some(start) => from + start,
+        None => text.len(),
+    }
+}
+
+impl Source {
+    /// Applies the given edits to this source, creating a new source with the
+    /// same name and options. Ranges of edits are given in terms of positions
+    /// of this source, and must not overlap. Normalized sources (see
+    /// [`SourceBuilder::finish_normalized`](super::SourceBuilder::finish_normalized))
+    /// cannot be edited.
+    ///
+    /// Only the regions around the edits are segmented again, since segments
+    /// can merge across an edit boundary. The indices of untouched regions
+    /// are shared with this source, shifted, rather than copied, so the cost
+    /// of an edit grows with the edited regions and the number of previous
+    /// edits, not with the source length. The contents are still copied.
+    /// Also returns a [`PositionMap`], which translates old locations and
+    /// spans into the new source.
+    pub fn apply_edits(
+        &self,
+        edits: &[TextEdit],
+    ) -> Result<(Source, PositionMap), EditError> {
+        if self.inner.normalization.is_some() {
+            return Err(EditError::Normalized);
+        }
+        let mut edits: Vec<_> = edits.iter().collect();
+        edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+        for edit in &edits {
+            if edit.range.start > edit.range.end || edit.range.end > self.len()
+            {
+                return Err(EditError::InvalidRange {
+                    range: edit.range(),
+                    len: self.len(),
+                });
+            }
+        }
+        for pair in edits.windows(2) {
+            if pair[0].range.end > pair[1].range.start {
+                return Err(EditError::Overlapping {
+                    first: pair[0].range(),
+                    second: pair[1].range(),
+                });
+            }
+        }
+
+        let byte_edits: Box<[_]> = edits
+            .iter()
+            .map(|edit| ByteEdit {
+                old: self.byte_index(edit.range.start)
+                    .. self.byte_index(edit.range.end),
+                new_len: edit.text.len(),
+            })
+            .collect();
+        let windows = self.edit_windows(&edits);
+        let new = self.build_edited(&edits, &byte_edits, &windows);
+        let map = PositionMap {
+            old: self.clone(),
+            new: new.clone(),
+            edits: byte_edits,
+        };
+        Ok((new, map))
+    }
+
+    /// Computes the windows of positions that must be segmented again. Each
+    /// window starts with one segment of context on each side of its edits,
+    /// and grows until the segment boundaries at both of its ends hold. When
+    /// growing to the right, only the unstable segments at the end of the
+    /// window (see [`Segmenter::unstable_segments`]) are segmented again
+    /// together with the new segment, so a window grows in time linear in its
+    /// length.
+    fn edit_windows(&self, edits: &[&TextEdit]) -> Vec<Window> {
+        let mut windows: Vec<Window> = Vec::new();
+        for (index, edit) in edits.iter().enumerate() {
+            let start = edit.range.start.saturating_sub(1);
+            let end = (edit.range.end + 1).min(self.len());
+            match windows.last_mut() {
+                Some(last) if start <= last.range.end => {
+                    last.range.end = last.range.end.max(end);
+                    last.edits.end = index + 1;
+                },
+                _ => windows.push(Window {
+                    range: start .. end,
+                    edits: index .. index + 1,
+                }),
+            }
+        }
+
+        let segmenter = self.segmenter();
+        let mut index = 0;
+        while index < windows.len() {
+            let mut text = self.window_text(&windows[index], edits);
+            // A boundary of the text which growing to the right cannot move,
+            // so that only the text after it is segmented again.
+            let mut stable = 0;
+            loop {
+                let Range { start, end } = windows[index].range;
+                let grow_right = end < self.len()
+                    && (text.is_empty() && start > 0
+                        || !has_boundary(
+                            &(text[stable ..].to_owned() + &self[end]),
+                            text.len() - stable,
+                            segmenter,
+                        ));
+                if grow_right {
+                    windows[index].range.end += 1;
+                    text.push_str(&self[end]);
+                    while index + 1 < windows.len()
+                        && windows[index + 1].range.start
+                            <= windows[index].range.end
+                    {
+                        let next = windows.remove(index + 1);
+                        windows[index].range.end = next.range.end;
+                        windows[index].edits.end = next.edits.end;
+                        text = self.window_text(&windows[index], edits);
+                    }
+                    stable = stable_boundary(&text, stable, segmenter);
+                    continue;
+                }
+
+                let grow_left = start > 0
+                    && !has_boundary(
+                        &(self[start - 1].to_owned() + &text),
+                        self[start - 1].len(),
+                        segmenter,
+                    );
+                if !grow_left {
+                    break;
+                }
+                windows[index].range.start -= 1;
+                if index > 0
+                    && windows[index].range.start
+                        <= windows[index - 1].range.end
+                {
+                    let current = windows.remove(index);
+                    index -= 1;
+                    windows[index].range.end = current.range.end;
+                    windows[index].edits.end = current.edits.end;
+                    text = self.window_text(&windows[index], edits);
+                } else {
+                    text.insert_str(0, &self[start - 1]);
+                }
+                // Prepending text may move any boundary.
+                stable = 0;
+            }
+            index += 1;
+        }
+        windows
+    }
+
+    /// Builds the new text of a window, with its edits applied.
+    fn window_text(&self, window: &Window, edits: &[&TextEdit]) -> String {
+        let mut text = String::new();
+        let mut position = window.range.start;
+        for edit in &edits[window.edits.clone()] {
+            text.push_str(&self[position .. edit.range.start]);
+            text.push_str(&edit.text);
+            position = edit.range.end;
+        }
+        text.push_str(&self[position .. window.range.end]);
+        text
+    }
+
+    /// Builds the edited source, sharing the indices outside of the windows
+    /// with this source and segmenting the windows' new text. Replaced
+    /// invalid UTF-8 stays recorded unless its segment was replaced by an
+    /// edit.
+    fn build_edited(
+        &self,
+        edits: &[&TextEdit],
+        byte_edits: &[ByteEdit],
+        windows: &[Window],
+    ) -> Source {
+        let mut contents = String::new();
+        let mut segments = SplicedIndexArrayBuilder::new();
+        let mut newlines = SplicedIndexArrayBuilder::new();
+
+        let mut position = 0;
+        for window in windows {
+            self.share_untouched(
+                position .. window.range.start,
+                contents.len(),
+                &mut segments,
+                &mut newlines,
+            );
+            contents.push_str(&self[position .. window.range.start]);
+            let text = self.window_text(window, edits);
+            let mut window_segments = IndexArrayBuilder::new();
+            let mut window_newlines = IndexArrayBuilder::new();
+            segment_into(
+                &text, 0, &*self.inner.segmenter, self.inner.line_break_policy,
+                &mut window_segments, &mut window_newlines,
+            );
+            newlines
+                .push_array(window_newlines.finish(), segments.len() as isize);
+            segments
+                .push_array(window_segments.finish(), contents.len() as isize);
+            contents.push_str(&text);
+            position = window.range.end;
+        }
+        self.share_untouched(
+            position .. self.len(),
+            contents.len(),
+            &mut segments,
+            &mut newlines,
+        );
+        contents.push_str(&self[position ..]);
+        let mut end = IndexArrayBuilder::new();
+        end.push(contents.len());
+        segments.push_array(end.finish(), 0);
+
+        let segments = segments.finish();
+        let invalid_utf8: Vec<_> = self
+            .inner
+            .invalid_utf8
+            .iter()
+            .filter_map(|position| {
+                let mut byte = self.byte_index(position);
+                for (edit, byte_edit) in edits.iter().zip(byte_edits) {
+                    if edit.range.contains(&position) {
+                        return None;
+                    }
+                    if edit.range.end <= position {
+                        byte = byte + byte_edit.new_len - byte_edit.old.len();
+                    }
+                }
+                Some(byte)
+            })
+            .collect();
+        let invalid_utf8 = containing_positions(
+            |byte_index| segments.binary_search(byte_index),
+            &invalid_utf8,
+        );
+
+        let inner = SourceInner {
+            name: self.inner.name.clone(),
+            path: self.inner.path.clone(),
+            file_id: self.inner.file_id,
+            contents: Contents::Owned(contents.into()),
+            line_break_policy: self.inner.line_break_policy,
+            case_folding: self.inner.case_folding,
+            segmenter: self.inner.segmenter.clone(),
+            table: SegmentTable::Spliced {
+                segments,
+                newlines: newlines.finish(),
+            },
+            invalid_utf8,
+            normalization: None,
+            stream_id: None,
+        };
+        Source { inner: Arc::new(inner) }
+    }
+
+    /// Creates a snapshot of a stream with the given text appended to this
+    /// one. The unstable segments at the end (see
+    /// [`Segmenter::unstable_segments`]) are segmented again, together with
+    /// the text, and so are the segments before them as long as the boundary
+    /// at their start does not hold, just like the window of an edit grows.
+    /// The indices of the other segments are shared with this source, and the
+    /// pieces sharing them merge as they pile up. The contents are written
+    /// after these ones in a buffer shared with them, if it has room and no
+    /// text was appended to this source yet, or copied into a buffer twice as
+    /// large otherwise. Appending to the last snapshot thus takes amortized
+    /// time linear in the text and the re-segmented tail, not in the source.
+    pub(crate) fn append(&self, text: &str) -> Source {
+        let edit = TextEdit::new(self.len() .. self.len(), text);
+        let start = match self.edit_windows(&[&edit]).first() {
+            Some(window) => window.range.start,
+            None => unreachable!("an edit always has a window"),
+        };
+        let unstable =
+            self.len().saturating_sub(self.segmenter().unstable_segments());
+        let start = start.min(unstable);
+        let byte_start = self.byte_index(start);
+        let mut segments = SplicedIndexArrayBuilder::new();
+        let mut newlines = SplicedIndexArrayBuilder::new();
+        self.share_untouched(0 .. start, 0, &mut segments, &mut newlines);
+
+        let tail = self[start ..].to_owned() + text;
+        let mut tail_segments = IndexArrayBuilder::new();
+        let mut tail_newlines = IndexArrayBuilder::new();
+        segment_into(
+            &tail, 0, &*self.inner.segmenter, self.inner.line_break_policy,
+            &mut tail_segments, &mut tail_newlines,
+        );
+        tail_segments.push(tail.len());
+        newlines
+            .push_array(tail_newlines.finish(), segments.len() as isize)
+            .merge_tail();
+        segments
+            .push_array(tail_segments.finish(), byte_start as isize)
+            .merge_tail();
+        let contents = match &self.inner.contents {
+            Contents::Appended(contents) => contents.append(text),
+            contents => AppendedContents::new(contents.as_str(), text),
+        };
+
+        let inner = SourceInner {
+            name: self.inner.name.clone(),
+            path: self.inner.path.clone(),
+            file_id: self.inner.file_id,
+            contents: Contents::Appended(contents),
+            line_break_policy: self.inner.line_break_policy,
+            case_folding: self.inner.case_folding,
+            segmenter: self.inner.segmenter.clone(),
+            table: SegmentTable::Spliced {
+                segments: segments.finish(),
+                newlines: newlines.finish(),
+            },
+            // Streams are built from strings, so they never record invalid
+            // UTF-8.
+            invalid_utf8: self.inner.invalid_utf8.clone(),
+            normalization: None,
+            stream_id: self.inner.stream_id.clone(),
+        };
+        Source { inner: Arc::new(inner) }
+    }
+
+    /// Marks this source, which must not be shared yet, as the first
+    /// snapshot of the stream with the given identity.
+    pub(crate) fn into_snapshot(mut self, stream_id: StreamId) -> Self {
+        match Arc::get_mut(&mut self.inner) {
+            Some(inner) => inner.stream_id = Some(stream_id),
+            None => unreachable!("Source {} is already shared", self),
+        }
+        self
+    }
+
+    /// Identity of the stream this source is a snapshot of, if any.
+    pub(crate) fn stream_id(&self) -> Option<&StreamId> {
+        self.inner.stream_id.as_ref()
+    }
+
+    /// Pushes the indices of the given range of positions, which no edit
+    /// touches, shifted to where the range lands, i.e. the given byte index
+    /// of the edited contents. The indices are shared with this source.
+    fn share_untouched(
+        &self,
+        positions: Range<usize>,
+        byte_start: usize,
+        segments: &mut SplicedIndexArrayBuilder,
+        newlines: &mut SplicedIndexArrayBuilder,
+    ) {
+        let byte_shift =
+            byte_start as isize - self.byte_index(positions.start) as isize;
+        let position_shift = segments.len() as isize - positions.start as isize;
+        self.inner.table.slice_into(
+            self.contents(),
+            positions.clone(),
+            byte_shift,
+            position_shift,
+            segments,
+            newlines,
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use super::{EditError, TextEdit};
+use crate::{
+    source::{NormalizationForm, Segmentation, SourceBuilder},
+    Location,
+    Source,
+};
+
+fn assert_same_indices(edited: &Source) {
+    let fresh = Source::new(edited.name(), edited.contents());
+    assert_eq!(
+        edited.seg_byte_indices().collect::<Vec<_>>(),
+        fresh.seg_byte_indices().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        edited.newline_indices().collect::<Vec<_>>(),
+        fresh.newline_indices().collect::<Vec<_>>()
+    );
+    for position in 0 ..= fresh.len() {
+        assert_eq!(
+            Location::new(edited.clone(), position).line_column(),
+            Location::new(fresh.clone(), position).line_column()
+        );
+    }
+    let byte_position = |source: &Source, byte_offset| {
+        source
+            .location_at_byte(byte_offset)
+            .map(|location| location.position())
+            .map_err(|error| error.containing().map(Location::position))
+    };
+    for byte_offset in 0 ..= fresh.contents().len() + 1 {
+        assert_eq!(
+            byte_position(edited, byte_offset),
+            byte_position(&fresh, byte_offset)
+        );
+    }
+}
+
+#[test]
+fn replace_and_insert() {
+    let source = Source::new("edit.txt", "av́e\nmař̋ia\ns̋ic̄");
+    let (edited, map) = source
+        .apply_edits(&[
+            TextEdit::new(4 .. 9, "jo̊e\nx"),
+            TextEdit::new(0 .. 0, "¡"),
+        ])
+        .unwrap();
+    assert_eq!(edited.contents(), "¡av́e\njo̊e\nx\ns̋ic̄");
+    assert_same_indices(&edited);
+
+    assert_eq!(map.map_position(0), Some(0));
+    assert_eq!(map.map_position(1), Some(2));
+    assert_eq!(map.map_position(4), Some(5));
+    assert_eq!(map.map_position(6), None);
+    assert_eq!(map.map_position(9), Some(10));
+    assert_eq!(map.map_position(13), Some(edited.len()));
+
+    let old_span = source.full_span().try_slice(10 .. 12).unwrap();
+    let new_span = map.map_span(&old_span).unwrap();
+    assert_eq!(new_span.as_str(), "s̋i");
+    assert_eq!(new_span.start().line_column(), (3, 0));
+}
+
+#[test]
+fn segments_merge_across_edits() {
+    let source = Source::new("merge.txt", "ab\rcd");
+    let (edited, map) = source
+        .apply_edits(&[
+            TextEdit::new(1 .. 2, "\u{301}"),
+            TextEdit::new(3 .. 3, "\n"),
+        ])
+        .unwrap();
+    assert_eq!(edited.contents(), "a\u{301}\r\ncd");
+    assert_same_indices(&edited);
+    assert_eq!(edited.len(), 4);
+    assert_eq!(edited.line_count(), 2);
+    assert_eq!(map.map_position(0), Some(0));
+    assert_eq!(map.map_position(2), Some(1));
+    assert_eq!(map.map_position(3), None);
+    assert_eq!(map.map_position(4), Some(3));
+}
+
+#[test]
+fn deletion_joins_neighbours() {
+    let source = Source::new("join.txt", "x\r-\ny");
+    let (edited, _) = source.apply_edits(&[TextEdit::new(2 .. 3, "")]).unwrap();
+    assert_eq!(edited.contents(), "x\r\ny");
+    assert_same_indices(&edited);
+    assert_eq!(edited.line_count(), 2);
+}
+
+#[test]
+fn invalid_edits() {
+    let source = Source::new("invalid.txt", "abcdef");
+    assert_eq!(
+        source.apply_edits(&[TextEdit::new(5 .. 7, "")]).unwrap_err(),
+        EditError::InvalidRange { range: 5 .. 7, len: 6 }
+    );
+    assert_eq!(
+        source
+            .apply_edits(&[
+                TextEdit::new(3 .. 5, ""),
+                TextEdit::new(1 .. 4, "")
+            ])
+            .unwrap_err(),
+        EditError::Overlapping { first: 1 .. 4, second: 3 .. 5 }
+    );
+
+    let normalized = SourceBuilder::new().finish_normalized(
+        "invalid.txt",
+        "e\u{301}",
+        NormalizationForm::Nfc,
+    );
+    assert_eq!(
+        normalized.apply_edits(&[TextEdit::new(0 .. 1, "a")]).unwrap_err(),
+        EditError::Normalized
+    );
+}
+
+#[test]
+fn repeated_edits() {
+    let contents = "uno\r\ndós\n\ntres e\u{301}\u{2028}cuatro\n".repeat(40);
+    let eager = Source::new("repeated.txt", contents.as_str());
+    let lazy = SourceBuilder::new()
+        .chunk_size(16)
+        .finish_lazy("repeated.txt", contents);
+    for mut source in [eager, lazy] {
+        // More edits than pieces a spliced table keeps before compacting.
+        for step in 0 .. 300 {
+            let position = step * 5 % source.len().max(1);
+            let end = (position + 2).min(source.len());
+            let edit = match step % 3 {
+                0 => TextEdit::new(position .. position, "\u{301}x\r"),
+                1 => TextEdit::new(position .. end, "\n"),
+                _ => TextEdit::new(position .. end, ""),
+            };
+            let (edited, _) = source.apply_edits(&[edit]).unwrap();
+            assert_same_indices(&edited);
+            source = edited;
+        }
+    }
+}
+
+#[test]
+fn window_grows_across_long_run() {
+    // Inserting a regional indicator pairs it with the next one, and so on up
+    // to the end of the run.
+    let source = Source::new("flags.txt", "\u{1f1e6}\u{1f1e8}".repeat(500));
+    let (edited, _) =
+        source.apply_edits(&[TextEdit::new(0 .. 0, "\u{1f1e6}")]).unwrap();
+    assert_same_indices(&edited);
+    assert_eq!(edited.len(), 501);
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Segmentation::Words);
+    let source = builder.finish("words.txt", "ab.cd ef:gh.1,2  i.j");
+    for position in 0 ..= source.len() {
+        for text in ["a", ".", " ", ":", "3"] {
+            let edit = TextEdit::new(position .. position, text);
+            let (edited, _) = source.apply_edits(&[edit]).unwrap();
+            let fresh = builder.finish("words.txt", edited.contents());
+            assert_eq!(
+                edited.seg_byte_indices().collect::<Vec<_>>(),
+                fresh.seg_byte_indices().collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -2,7 +2,6 @@
 
 use super::Source;
 use std::{
-    cmp::Ordering,
     convert::TryFrom,
     fmt,
     ops::{
@@ -14,6 +13,7 @@ use std::{
         RangeToInclusive,
     },
     slice,
+    sync::Arc,
 };
 
 /// Builder of an [`IndexArray`].
@@ -66,7 +66,14 @@ impl IndexArrayBuilder {
         self.as_u32.sort();
         self.as_u64.sort();
 
+        let mut tier_ends = [0; 4];
+        tier_ends[0] = self.as_u8.len();
+        tier_ends[1] = tier_ends[0] + self.as_u16.len();
+        tier_ends[2] = tier_ends[1] + self.as_u32.len();
+        tier_ends[3] = tier_ends[2] + self.as_u64.len();
+
         IndexArray {
+            tier_ends,
             as_u8: self.as_u8.into(),
             as_u16: self.as_u16.into(),
             as_u32: self.as_u32.into(),
@@ -83,8 +90,15 @@ impl From<IndexArrayBuilder> for IndexArray {
 
 /// A smart ordered array of indices, which tries to use space as little as
 /// possible.
+///
+/// Indices are stored in tiers by the width they fit in. Since the array is
+/// ordered, each tier holds a contiguous range of meta-indices, whose ends
+/// are computed upfront, so accessing an index only compares the meta-index
+/// with them, and searching an index only searches the tier of its width.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct IndexArray {
+    /// Meta-index where each tier ends, the last one being the length.
+    tier_ends: [usize; 4],
     /// Indices that fit 8 bits.
     as_u8: Box<[u8]>,
     /// Indices that fit 16 bits.
@@ -98,10 +112,7 @@ pub struct IndexArray {
 impl IndexArray {
     /// Length of this array.
     pub fn len(&self) -> usize {
-        self.as_u8.len()
-            + self.as_u16.len()
-            + self.as_u32.len()
-            + self.as_u64.len()
+        self.tier_ends[3]
     }
 
     /// Gets an index stored in the array given this meta-index.
@@ -121,48 +132,47 @@ impl IndexArray {
 
     /// Gets an index stored in the array given this meta-index, returning
     /// `None` if out of bounds.
-    pub fn get(&self, mut meta_index: usize) -> Option<usize> {
-        if let Some(&i) = self.as_u8.get(meta_index) {
-            return Some(i as usize);
-        }
-        meta_index -= self.as_u8.len();
-        if let Some(&i) = self.as_u16.get(meta_index) {
-            return Some(i as usize);
-        }
-        meta_index -= self.as_u16.len();
-        if let Some(&i) = self.as_u32.get(meta_index) {
-            return Some(i as usize);
-        }
-        meta_index -= self.as_u32.len();
-        self.as_u64.get(meta_index).map(|&i| i as usize)
+    pub fn get(&self, meta_index: usize) -> Option<usize> {
+        let [u8_end, u16_end, u32_end, len] = self.tier_ends;
+        let index = if meta_index < u8_end {
+            self.as_u8[meta_index] as usize
+        } else if meta_index < u16_end {
+            self.as_u16[meta_index - u8_end] as usize
+        } else if meta_index < u32_end {
+            self.as_u32[meta_index - u16_end] as usize
+        } else if meta_index < len {
+            self.as_u64[meta_index - u32_end] as usize
+        } else {
+            return None;
+        };
+        Some(index)
     }
 
     /// Performs a binary search on this index array. `Ok` means it was found,
     /// `Err` means it was not found, but we have the position where it would
     /// be.
+    ///
+    /// Only the tier of the element's width is searched: every index of a
+    /// previous tier is smaller, and every index of a following tier is
+    /// greater.
     pub fn binary_search(&self, elem: usize) -> Result<usize, usize> {
-        let mut low = 0;
-        let mut high = self.len();
-        let mut error = 0;
-        while low < high {
-            let mid = low + (high - low) / 2;
-            match self.index(mid).cmp(&elem) {
-                Ordering::Equal => return Ok(mid),
-                Ordering::Greater => {
-                    high = mid;
-                    error = high
-                },
-                Ordering::Less => {
-                    low = mid + 1;
-                    error = low;
-                },
-            }
+        let (result, tier_start) = if let Ok(elem) = u8::try_from(elem) {
+            (self.as_u8.binary_search(&elem), 0)
+        } else if let Ok(elem) = u16::try_from(elem) {
+            (self.as_u16.binary_search(&elem), self.tier_ends[0])
+        } else if let Ok(elem) = u32::try_from(elem) {
+            (self.as_u32.binary_search(&elem), self.tier_ends[1])
+        } else {
+            (self.as_u64.binary_search(&(elem as u64)), self.tier_ends[2])
+        };
+        match result {
+            Ok(index) => Ok(tier_start + index),
+            Err(index) => Err(tier_start + index),
         }
-        Err(error)
     }
 
     /// Iterates over the indices stored in this array.
-    pub fn iter(&self) -> IndexArrayIter {
+    pub fn iter(&self) -> IndexArrayIter<'_> {
         IndexArrayIter {
             as_u8: self.as_u8.iter(),
             as_u16: self.as_u16.iter(),
@@ -170,6 +180,15 @@ impl IndexArray {
             as_u64: self.as_u64.iter(),
         }
     }
+
+    /// Number of bytes this array allocates on the heap.
+    #[cfg(test)]
+    fn heap_size(&self) -> usize {
+        self.as_u8.len()
+            + self.as_u16.len() * 2
+            + self.as_u32.len() * 4
+            + self.as_u64.len() * 8
+    }
 }
 
 impl<'array> IntoIterator for &'array IndexArray {
@@ -217,21 +236,239 @@ impl<'array> Iterator for IndexArrayIter<'array> {
 
 impl<'array> DoubleEndedIterator for IndexArrayIter<'array> {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if let Some(&i) = self.as_u64.next() {
+        if let Some(&i) = self.as_u64.next_back() {
             return Some(i as usize);
         }
-        if let Some(&i) = self.as_u32.next() {
+        if let Some(&i) = self.as_u32.next_back() {
             return Some(i as usize);
         }
-        if let Some(&i) = self.as_u16.next() {
+        if let Some(&i) = self.as_u16.next_back() {
             return Some(i as usize);
         }
-        self.as_u8.next().map(|&i| i as usize)
+        self.as_u8.next_back().map(|&i| i as usize)
     }
 }
 
 impl<'array> ExactSizeIterator for IndexArrayIter<'array> {}
 
+/// Maximum number of pieces of a [`SplicedIndexArray`]. Beyond it, the pieces
+/// are copied into a single array, so that lookups stay fast after many
+/// edits while the copy is amortized over them.
+const MAX_PIECES: usize = 256;
+
+/// Builder of a [`SplicedIndexArray`].
+#[derive(Debug, Clone, Default)]
+pub struct SplicedIndexArrayBuilder {
+    /// Pieces pushed so far, which must be in ascending order.
+    pieces: Vec<Piece>,
+}
+
+impl SplicedIndexArrayBuilder {
+    /// Creates a new builder with no elements.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The length of this builder.
+    pub fn len(&self) -> usize {
+        self.pieces.iter().map(|piece| piece.range.len()).sum()
+    }
+
+    /// Pushes the indices of a whole array, shifted by the given amount.
+    pub fn push_array(&mut self, array: IndexArray, shift: isize) -> &mut Self {
+        let len = array.len();
+        self.push_shared(&Arc::new(array), 0 .. len, shift)
+    }
+
+    /// Pushes the indices of the given range of meta-indices of a shared
+    /// array, shifted by the given amount, without copying them.
+    pub fn push_shared(
+        &mut self,
+        array: &Arc<IndexArray>,
+        range: Range<usize>,
+        shift: isize,
+    ) -> &mut Self {
+        if !range.is_empty() {
+            self.pieces.push(Piece { array: array.clone(), range, shift });
+        }
+        self
+    }
+
+    /// Merges the last pieces into one while the last is at least half as
+    /// long as the one before it, copying their indices. Merging after each
+    /// push of a short array, as when text is appended repeatedly, keeps
+    /// logarithmically many pieces, copying each index a logarithmic number of
+    /// times.
+    pub fn merge_tail(&mut self) -> &mut Self {
+        while let [.., before, last] = &self.pieces[..] {
+            if last.range.len() * 2 < before.range.len() {
+                break;
+            }
+            let start = self.pieces.len() - 2;
+            let merged = Piece::flatten(&self.pieces[start ..]);
+            self.pieces.truncate(start);
+            self.pieces.push(merged);
+        }
+        self
+    }
+
+    /// Finishes the builder and creates a [`SplicedIndexArray`].
+    pub fn finish(self) -> SplicedIndexArray {
+        let pieces = if self.pieces.len() > MAX_PIECES {
+            vec![Piece::flatten(&self.pieces)]
+        } else {
+            self.pieces
+        };
+        let piece_ends = pieces
+            .iter()
+            .scan(0, |end, piece| {
+                *end += piece.range.len();
+                Some(*end)
+            })
+            .collect();
+        SplicedIndexArray { pieces: pieces.into(), piece_ends }
+    }
+}
+
+/// An ordered array of indices made of slices of shared [`IndexArray`]s,
+/// each shifted by some amount. Copies of an array where a few ranges of
+/// indices changed share the other ranges instead of copying them.
+///
+/// Accessing or searching an index first searches its piece, so it is
+/// logarithmic in the number of pieces.
+#[derive(Debug, Clone)]
+pub struct SplicedIndexArray {
+    /// Pieces of this array, in ascending order.
+    pieces: Box<[Piece]>,
+    /// Meta-index where each piece ends, the last one being the length.
+    piece_ends: Box<[usize]>,
+}
+
+impl SplicedIndexArray {
+    /// Length of this array.
+    pub fn len(&self) -> usize {
+        self.piece_ends.last().copied().unwrap_or(0)
+    }
+
+    /// Gets an index stored in the array given this meta-index, returning
+    /// `None` if out of bounds.
+    pub fn get(&self, meta_index: usize) -> Option<usize> {
+        let piece_index =
+            self.piece_ends.partition_point(|&end| end <= meta_index);
+        let piece = self.pieces.get(piece_index)?;
+        Some(piece.get(meta_index - self.piece_start(piece_index)))
+    }
+
+    /// Performs a binary search on this index array. `Ok` means it was found,
+    /// `Err` means it was not found, but we have the position where it would
+    /// be.
+    pub fn binary_search(&self, elem: usize) -> Result<usize, usize> {
+        let piece_index =
+            self.pieces.partition_point(|piece| piece.last() < elem);
+        let piece = match self.pieces.get(piece_index) {
+            Some(piece) => piece,
+            None => return Err(self.len()),
+        };
+        let start = self.piece_start(piece_index);
+        match piece.binary_search(elem) {
+            Ok(offset) => Ok(start + offset),
+            Err(offset) => Err(start + offset),
+        }
+    }
+
+    /// Pushes the indices of the given range of meta-indices onto a builder,
+    /// shifted by the given amount, sharing the arrays of the pieces.
+    pub fn slice_into(
+        &self,
+        range: Range<usize>,
+        shift: isize,
+        builder: &mut SplicedIndexArrayBuilder,
+    ) {
+        for (piece_index, piece) in self.pieces.iter().enumerate() {
+            let start = self.piece_start(piece_index);
+            let end = self.piece_ends[piece_index];
+            if end <= range.start {
+                continue;
+            }
+            if start >= range.end {
+                break;
+            }
+            let first = piece.range.start + range.start.max(start) - start;
+            let last = piece.range.start + range.end.min(end) - start;
+            builder.push_shared(
+                &piece.array,
+                first .. last,
+                piece.shift + shift,
+            );
+        }
+    }
+
+    /// Meta-index where the given piece starts.
+    fn piece_start(&self, piece_index: usize) -> usize {
+        match piece_index {
+            0 => 0,
+            _ => self.piece_ends[piece_index - 1],
+        }
+    }
+}
+
+/// A non-empty slice of a shared [`IndexArray`], shifted by some amount.
+#[derive(Debug, Clone)]
+struct Piece {
+    /// The shared array.
+    array: Arc<IndexArray>,
+    /// Range of meta-indices of the shared array in this piece.
+    range: Range<usize>,
+    /// Amount added to the indices of the shared array.
+    shift: isize,
+}
+
+impl Piece {
+    /// Copies the indices of the given pieces, in order, into a single piece.
+    fn flatten(pieces: &[Piece]) -> Self {
+        let mut builder = IndexArrayBuilder::new();
+        for piece in pieces {
+            for offset in 0 .. piece.range.len() {
+                builder.push(piece.get(offset));
+            }
+        }
+        let array = builder.finish();
+        let len = array.len();
+        Self { array: Arc::new(array), range: 0 .. len, shift: 0 }
+    }
+
+    /// Gets the index at the given offset from the start of this piece.
+    fn get(&self, offset: usize) -> usize {
+        self.array
+            .index(self.range.start + offset)
+            .wrapping_add_signed(self.shift)
+    }
+
+    /// The last index of this piece.
+    fn last(&self) -> usize {
+        self.get(self.range.len() - 1)
+    }
+
+    /// Performs a binary search on this piece, as in
+    /// [`IndexArray::binary_search`], yielding offsets from its start.
+    fn binary_search(&self, elem: usize) -> Result<usize, usize> {
+        let unshifted = if self.shift >= 0 {
+            elem.checked_sub(self.shift.unsigned_abs())
+        } else {
+            elem.checked_add(self.shift.unsigned_abs())
+        };
+        let unshifted = match unshifted {
+            Some(unshifted) => unshifted,
+            None => return Err(0),
+        };
+        let Range { start, end } = self.range;
+        match self.array.binary_search(unshifted) {
+            Ok(index) if self.range.contains(&index) => Ok(index - start),
+            Ok(index) | Err(index) => Err(index.clamp(start, end) - start),
+        }
+    }
+}
+
 /// An index on a source code.
 pub trait SourceIndex: fmt::Debug {
     /// Output of the indexing operation.
@@ -266,8 +503,8 @@ impl SourceIndex for Range<usize> {
     type Output = str;
 
     fn get<'src>(&self, src: &'src Source) -> Option<&'src Self::Output> {
-        let start = src.inner.segments.get(self.start)?;
-        let end = src.inner.segments.get(self.end)?;
+        let start = src.try_byte_index(self.start)?;
+        let end = src.try_byte_index(self.end)?;
         src.contents().get(start .. end)
     }
 }
@@ -314,7 +551,8 @@ impl SourceIndex for RangeFull {
 
 #[cfg(test)]
 mod test {
-    use super::IndexArrayBuilder;
+    use super::{IndexArrayBuilder, SplicedIndexArrayBuilder};
+    use std::sync::Arc;
 
     #[test]
     fn binary_search() {
@@ -333,4 +571,100 @@ mod test {
         assert_eq!(array.binary_search(9), Ok(5));
         assert_eq!(array.binary_search(10), Err(6));
     }
+
+    #[test]
+    fn binary_search_across_tiers() {
+        let indices = [0, 255, 256, 300, 65535, 65536, 1 << 20, 1 << 33];
+        let mut builder = IndexArrayBuilder::new();
+        for &index in &indices {
+            builder.push(index);
+        }
+        let array = builder.finish();
+        for (meta_index, &index) in indices.iter().enumerate() {
+            assert_eq!(array.get(meta_index), Some(index));
+            assert_eq!(array.binary_search(index), Ok(meta_index));
+        }
+        assert_eq!(array.get(indices.len()), None);
+        assert_eq!(array.binary_search(254), Err(1));
+        assert_eq!(array.binary_search(257), Err(3));
+        assert_eq!(array.binary_search(65534), Err(4));
+        assert_eq!(array.binary_search(70000), Err(6));
+        assert_eq!(array.binary_search(1 << 34), Err(8));
+        assert!(array.iter().rev().eq(indices.iter().rev().copied()));
+    }
+
+    #[test]
+    fn reverse_iteration() {
+        let indices = [3, 7, 300, 301, 70000, 70001];
+        let mut builder = IndexArrayBuilder::new();
+        for &index in &indices {
+            builder.push(index);
+        }
+        let array = builder.finish();
+        let reversed: Vec<_> = array.iter().rev().collect();
+        assert_eq!(reversed, [70001, 70000, 301, 300, 7, 3]);
+
+        let mut iter = array.iter();
+        assert_eq!(iter.next_back(), Some(70001));
+        assert_eq!(iter.next(), Some(3));
+        assert_eq!(iter.next_back(), Some(70000));
+        assert_eq!(iter.next_back(), Some(301));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(iter.next(), Some(7));
+        assert_eq!(iter.next_back(), Some(300));
+        assert_eq!(iter.next_back(), None);
+        assert_eq!(iter.next(), None);
+    }
+
+    #[test]
+    fn spliced_slices() {
+        let mut builder = IndexArrayBuilder::new();
+        builder.push(0).push(2).push(5).push(300).push(301);
+        let array = Arc::new(builder.finish());
+        let mut builder = IndexArrayBuilder::new();
+        builder.push(0).push(1);
+        let mut spliced = SplicedIndexArrayBuilder::new();
+        spliced
+            .push_shared(&array, 0 .. 2, 0)
+            .push_array(builder.finish(), 3)
+            .push_shared(&array, 2 .. 2, 0)
+            .push_shared(&array, 3 .. 5, -290);
+        let spliced = spliced.finish();
+        let indices = [0, 2, 3, 4, 10, 11];
+        assert_eq!(spliced.len(), indices.len());
+        for (meta_index, &index) in indices.iter().enumerate() {
+            assert_eq!(spliced.get(meta_index), Some(index));
+            assert_eq!(spliced.binary_search(index), Ok(meta_index));
+        }
+        assert_eq!(spliced.get(indices.len()), None);
+        assert_eq!(spliced.binary_search(1), Err(1));
+        assert_eq!(spliced.binary_search(7), Err(4));
+        assert_eq!(spliced.binary_search(12), Err(6));
+
+        let mut sliced = SplicedIndexArrayBuilder::new();
+        spliced.slice_into(1 .. 5, 10, &mut sliced);
+        let sliced = sliced.finish();
+        assert_eq!(sliced.len(), 4);
+        assert_eq!(
+            (0 .. sliced.len()).map(|i| sliced.get(i)).collect::<Vec<_>>(),
+            [Some(12), Some(13), Some(14), Some(20)]
+        );
+        assert_eq!(sliced.binary_search(2), Err(0));
+        assert_eq!(sliced.binary_search(20), Ok(3));
+    }
+
+    #[test]
+    fn memory_by_width() {
+        // Byte indices of a 1 MiB source with a segment every 3 bytes.
+        let mut builder = IndexArrayBuilder::new();
+        for index in (0 .. 1 << 20).step_by(3) {
+            builder.push(index);
+        }
+        let array = builder.finish();
+        let u8_count = 256usize.div_ceil(3);
+        let u16_count = 65536usize.div_ceil(3) - u8_count;
+        let u32_count = array.len() - u8_count - u16_count;
+        assert_eq!(array.heap_size(), u8_count + u16_count * 2 + u32_count * 4);
+        assert!(array.heap_size() * 2 <= array.len() * size_of::<usize>());
+    }
 }
@@ -0,0 +1,171 @@
+//! Exports loading of source code objects from files and readers.
+
+use super::{buffer::AppendedContents, InvalidUtf8Locations, Source};
+#[cfg(feature = "mmap")]
+use memmap2::Mmap;
+#[cfg(feature = "mmap")]
+use std::str;
+use std::{error::Error, fmt, io, io::Read};
+
+/// Contents of a source, either owned, appended to a stream or memory-mapped.
+#[derive(Debug)]
+pub(super) enum Contents {
+    /// Contents owned in memory.
+    Owned(Box<str>),
+    /// Contents of a stream, sharing a buffer with the other snapshots.
+    Appended(AppendedContents),
+    /// Contents of a memory-mapped file, starting after a byte order mark
+    /// (if any). Validated as UTF-8 when mapped.
+    #[cfg(feature = "mmap")]
+    Mapped {
+        /// The mapped file.
+        map: Mmap,
+        /// Byte index where the contents start.
+        start: usize,
+    },
+}
+
+impl Contents {
+    /// The contents as a string.
+    pub(super) fn as_str(&self) -> &str {
+        match self {
+            Self::Owned(contents) => contents,
+            Self::Appended(contents) => contents.as_str(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped { map, start } => {
+                // SAFETY: mapped contents are validated as UTF-8 on creation.
+                unsafe { str::from_utf8_unchecked(&map[*start ..]) }
+            },
+        }
+    }
+}
+
+/// The UTF-8 byte order mark.
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// How invalid UTF-8 is handled when loading a source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum Utf8Policy {
+    /// Invalid UTF-8 is an error ([`LoadError::InvalidUtf8`]).
+    #[default]
+    Strict,
+    /// Each invalid UTF-8 sequence is replaced by the replacement character
+    /// (`U+FFFD`), and the replacements are recorded (see
+    /// [`Source::invalid_utf8`]).
+    Lossy,
+}
+
+/// Error returned when a source cannot be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// Reading the file or reader failed.
+    Io(io::Error),
+    /// The contents are not valid UTF-8, under [`Utf8Policy::Strict`].
+    InvalidUtf8(InvalidUtf8Error),
+}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(fmtr, "could not read source: {}", error),
+            Self::InvalidUtf8(error) => write!(fmtr, "{}", error),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::InvalidUtf8(error) => Some(error),
+        }
+    }
+}
+
+/// Error returned when loaded contents are not valid UTF-8. Holds the
+/// contents decoded lossily, so the invalid sequences can be reported as
+/// locations.
+#[derive(Debug, Clone)]
+pub struct InvalidUtf8Error {
+    /// The lossily decoded source.
+    decoded_source: Source,
+}
+
+impl InvalidUtf8Error {
+    /// Creates the error from a lossily decoded source.
+    pub(super) fn new(decoded_source: Source) -> Self {
+        Self { decoded_source }
+    }
+
+    /// The source decoded as in [`Utf8Policy::Lossy`]. Named apart from
+    /// [`Error::source`], which returns the cause of an error.
+    pub fn decoded_source(&self) -> &Source {
+        &self.decoded_source
+    }
+
+    /// Iterator over the locations of the segments where invalid UTF-8 was
+    /// replaced in the lossily decoded source.
+    pub fn locations(&self) -> InvalidUtf8Locations<'_> {
+        self.decoded_source.invalid_utf8()
+    }
+}
+
+impl fmt::Display for InvalidUtf8Error {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        let mut locations = self.locations();
+        let count = locations.len();
+        if let Some(first) = locations.next() {
+            write!(fmtr, "invalid UTF-8 {}", first)?;
+            if count > 1 {
+                write!(fmtr, " and {} more places", count - 1)?;
+            }
+            Ok(())
+        } else {
+            write!(fmtr, "invalid UTF-8 in {}", self.decoded_source)
+        }
+    }
+}
+
+impl Error for InvalidUtf8Error {}
+
+/// Reads all bytes of a reader.
+pub(super) fn read_bytes<R>(mut reader: R) -> io::Result<Vec<u8>>
+where
+    R: Read,
+{
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Length of the byte order mark at the start of the given bytes, if any.
+pub(super) fn bom_len(bytes: &[u8]) -> usize {
+    if bytes.starts_with(BOM) {
+        BOM.len()
+    } else {
+        0
+    }
+}
+
+/// Decodes the bytes as UTF-8 after stripping a byte order mark, replacing
+/// each invalid sequence by `U+FFFD`. Also returns the byte indices of the
+/// replacements in the decoded string.
+pub(super) fn decode_lossy(bytes: &[u8]) -> (Box<str>, Vec<usize>) {
+    let bytes = &bytes[bom_len(bytes) ..];
+    let mut contents = String::with_capacity(bytes.len());
+    let mut invalid = Vec::new();
+    for chunk in bytes.utf8_chunks() {
+        contents.push_str(chunk.valid());
+        if !chunk.invalid().is_empty() {
+            invalid.push(contents.len());
+            contents.push(char::REPLACEMENT_CHARACTER);
+        }
+    }
+    (contents.into(), invalid)
+}
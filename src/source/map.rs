@@ -0,0 +1,426 @@
+//! Exports a registry of multiple source code objects.
+
+#[cfg(test)]
+mod test;
+
+use super::{LoadError, Source, SourceBuilder};
+use crate::{location::Location, span::Span};
+use std::{
+    collections::HashMap,
+    convert::TryFrom,
+    fmt,
+    iter::Enumerate,
+    ops::Index,
+    path::Path,
+    slice,
+    sync::OnceLock,
+};
+
+/// A compact identifier of a source code object registered in a
+/// [`SourceMap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileId(u32);
+
+impl FileId {
+    /// Creates a file ID from the given index in a source map.
+    ///
+    /// # Panics
+    /// Panics if the index does not fit 32 bits.
+    fn from_index(index: usize) -> Self {
+        match u32::try_from(index) {
+            Ok(index) => Self(index),
+            Err(_) => panic!("Too many files in a source map: {}", index),
+        }
+    }
+
+    /// The index of the source in the source map it was registered at.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl fmt::Display for FileId {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "#{}", self.0)
+    }
+}
+
+/// A location in a source code object of a [`SourceMap`], carrying the
+/// [`FileId`] rather than the source. It is as small as two integers and
+/// `Copy`, so it is cheap to store in syntax trees of many files;
+/// [`SourceMap::resolve_location`] turns it back into a [`Location`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileLocation {
+    /// ID of the source code object.
+    file_id: FileId,
+    /// The string segment position.
+    position: usize,
+}
+
+impl FileLocation {
+    /// Creates a location given the file ID and position index in the source.
+    /// The position is checked only when resolved in a [`SourceMap`].
+    pub fn new(file_id: FileId, position: usize) -> Self {
+        Self { file_id, position }
+    }
+
+    /// The ID of the source code object.
+    pub fn file_id(self) -> FileId {
+        self.file_id
+    }
+
+    /// This location's position in the source code in terms of grapheme
+    /// clusters/segments.
+    pub fn position(self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for FileLocation {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "{}:{}", self.file_id, self.position)
+    }
+}
+
+/// A span in a source code object of a [`SourceMap`], carrying the [`FileId`]
+/// rather than the source, like [`FileLocation`].
+/// [`SourceMap::resolve_span`] turns it back into a [`Span`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FileSpan {
+    /// Start location.
+    start: FileLocation,
+    /// Length in string segments.
+    length: usize,
+}
+
+impl FileSpan {
+    /// Creates a span given its start location and length in segments. The
+    /// span is checked only when resolved in a [`SourceMap`].
+    pub fn new(start: FileLocation, length: usize) -> Self {
+        Self { start, length }
+    }
+
+    /// The ID of the source code object.
+    pub fn file_id(self) -> FileId {
+        self.start.file_id
+    }
+
+    /// The start location of this span.
+    pub fn start(self) -> FileLocation {
+        self.start
+    }
+
+    /// The end location of this span.
+    pub fn end(self) -> FileLocation {
+        FileLocation::new(self.start.file_id, self.start.position + self.length)
+    }
+
+    /// The length of this span in string segments.
+    pub fn len(self) -> usize {
+        self.length
+    }
+
+    /// Tests whether this span contains no segments.
+    pub fn is_empty(self) -> bool {
+        self.length == 0
+    }
+}
+
+impl fmt::Display for FileSpan {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "{}..{}", self.start, self.end().position)
+    }
+}
+
+/// A registry owning many source code objects, such as all files loaded by a
+/// compiler. Each source is identified by a compact [`FileId`], and all
+/// sources share a single "global" position space, such that a single
+/// `usize` is enough to find both the source and the location in it.
+///
+/// Where a source starts in the global position space depends on the lengths
+/// of the sources before it, so it is only computed once global positions are
+/// used, and lazily indexed sources are not fully indexed when registered.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    /// Registered sources, indexed by file IDs.
+    sources: Vec<Source>,
+    /// Global position where each source starts, in the same order as
+    /// `sources`. Starts are computed in order as needed, so the computed
+    /// ones are a prefix.
+    starts: Vec<OnceLock<usize>>,
+    /// Mapping from source names to file IDs.
+    by_name: HashMap<Box<str>, FileId>,
+}
+
+impl SourceMap {
+    /// Creates a new empty source map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of sources registered in this map.
+    pub fn len(&self) -> usize {
+        self.sources.len()
+    }
+
+    /// Tests whether no source is registered in this map.
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    /// Creates a new source code object given its name and its contents,
+    /// registers it in this map and returns its file ID. If there is already
+    /// a source with the same name, it is kept and its file ID is returned
+    /// instead, ignoring the given contents.
+    pub fn add<S0, S1>(&mut self, name: S0, contents: S1) -> FileId
+    where
+        S0: Into<Box<str>>,
+        S1: Into<Box<str>>,
+    {
+        self.add_with(&SourceBuilder::new(), name, contents)
+    }
+
+    /// Creates a new source code object given its name and its contents using
+    /// the options of the given builder, registers it in this map and
+    /// returns its file ID. If there is already a source with the same name,
+    /// its file ID is returned instead (see [`SourceMap::add`]).
+    pub fn add_with<S0, S1>(
+        &mut self,
+        builder: &SourceBuilder,
+        name: S0,
+        contents: S1,
+    ) -> FileId
+    where
+        S0: Into<Box<str>>,
+        S1: Into<Box<str>>,
+    {
+        let name = name.into();
+        if let Some(id) = self.find(&name) {
+            return id;
+        }
+        let id = FileId::from_index(self.sources.len());
+        let source = builder.create(name, None, contents.into(), &[], Some(id));
+        self.register(id, source)
+    }
+
+    /// Loads a source code object from the file at the given path, registers
+    /// it in this map and returns its file ID. The source is named after the
+    /// path. If there is already a source with the same name, the file is not
+    /// loaded and the file ID of that source is returned instead. See
+    /// [`Source::from_path`].
+    pub fn load<P>(&mut self, path: P) -> Result<FileId, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        self.load_with(&SourceBuilder::new(), path)
+    }
+
+    /// Loads a source code object from the file at the given path using the
+    /// options of the given builder, registers it in this map and returns its
+    /// file ID, unless there is already a source named after the path (see
+    /// [`SourceMap::load`]). See [`SourceBuilder::from_path`].
+    pub fn load_with<P>(
+        &mut self,
+        builder: &SourceBuilder,
+        path: P,
+    ) -> Result<FileId, LoadError>
+    where
+        P: AsRef<Path>,
+    {
+        // Sources loaded from paths are named after them.
+        if let Some(id) = self.find(&path.as_ref().display().to_string()) {
+            return Ok(id);
+        }
+        let id = FileId::from_index(self.sources.len());
+        let source = builder.load_path(path.as_ref(), Some(id))?;
+        Ok(self.register(id, source))
+    }
+
+    /// Registers a source created with the given file ID, which must be the
+    /// next one.
+    fn register(&mut self, id: FileId, source: Source) -> FileId {
+        let start = OnceLock::new();
+        if self.starts.is_empty() {
+            let _ = start.set(0);
+        }
+        self.by_name.insert(source.name().into(), id);
+        self.starts.push(start);
+        self.sources.push(source);
+        id
+    }
+
+    /// Global position where the source at the given index starts, computing
+    /// the starts before it as needed.
+    fn start(&self, index: usize) -> usize {
+        let computed = self.starts[..= index]
+            .partition_point(|start| start.get().is_some());
+        let mut start = 0;
+        // The first start is always computed.
+        for current in computed - 1 ..= index {
+            start = *self.starts[current].get_or_init(|| {
+                // The end of each source is a valid location, so one extra
+                // position is reserved for it.
+                start + self.sources[current - 1].len() + 1
+            });
+        }
+        start
+    }
+
+    /// Gets the source of the given file ID, returning `None` if the ID is not
+    /// from this map.
+    pub fn get(&self, id: FileId) -> Option<&Source> {
+        self.sources.get(id.index())
+    }
+
+    /// Finds the file ID of a source given its name.
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Finds a source given its name.
+    pub fn get_by_name(&self, name: &str) -> Option<&Source> {
+        self.find(name).and_then(|id| self.get(id))
+    }
+
+    /// Tests whether the given source is registered in this map.
+    pub fn contains(&self, source: &Source) -> bool {
+        source.file_id().and_then(|id| self.get(id)) == Some(source)
+    }
+
+    /// Creates a location given the file ID and position index in the source.
+    ///
+    /// # Panics
+    /// Panics if the file ID is not from this map or if `position` is past
+    /// beyond source length.
+    pub fn location(&self, id: FileId, position: usize) -> FileLocation {
+        let length = self[id].len();
+        if position > length {
+            panic!(
+                "Location position is too big; availabe: {}, given: {}",
+                length, position,
+            );
+        }
+        FileLocation::new(id, position)
+    }
+
+    /// Returns a span covering the whole source of the given file ID.
+    ///
+    /// # Panics
+    /// Panics if the file ID is not from this map.
+    pub fn full_span(&self, id: FileId) -> FileSpan {
+        FileSpan::new(FileLocation::new(id, 0), self[id].len())
+    }
+
+    /// Resolves a location carrying a file ID into a location holding the
+    /// source. Returns `None` if the file ID is not from this map or the
+    /// position is past beyond source length.
+    pub fn resolve_location(&self, location: FileLocation) -> Option<Location> {
+        let source = self.get(location.file_id)?;
+        if location.position > source.len() {
+            return None;
+        }
+        Some(Location::new_unchecked(source.clone(), location.position))
+    }
+
+    /// Resolves a span carrying a file ID into a span holding the source.
+    /// Returns `None` if the file ID is not from this map or the span ends
+    /// past beyond source length.
+    pub fn resolve_span(&self, span: FileSpan) -> Option<Span> {
+        let end = self.resolve_location(span.end())?;
+        let start =
+            Location::new_unchecked(end.source().clone(), span.start.position);
+        Some(Span::from_range(start, end))
+    }
+
+    /// Converts the given location into a global position, valid across all
+    /// sources of this map. Returns `None` if the location's source is not
+    /// registered in this map.
+    pub fn global_position(&self, location: &Location) -> Option<usize> {
+        let source = location.source();
+        if !self.contains(source) {
+            return None;
+        }
+        let id = source.file_id()?;
+        Some(self.start(id.index()) + location.position())
+    }
+
+    /// Resolves a global position back to the source and location it refers
+    /// to. Returns `None` if the position is beyond all sources.
+    pub fn resolve(&self, global: usize) -> Option<(Source, Location)> {
+        let mut index = self.starts.partition_point(|start| {
+            start.get().is_some_and(|&start| start <= global)
+        });
+        while index < self.sources.len() && self.start(index) <= global {
+            index += 1;
+        }
+        let index = index.checked_sub(1)?;
+        let source = &self.sources[index];
+        let position = global - self.start(index);
+        if position > source.len() {
+            return None;
+        }
+        let location = Location::new_unchecked(source.clone(), position);
+        Some((source.clone(), location))
+    }
+
+    /// Iterates over the sources of this map together with their file IDs.
+    pub fn iter(&self) -> Sources<'_> {
+        Sources { inner: self.sources.iter().enumerate() }
+    }
+}
+
+impl Index<FileId> for SourceMap {
+    type Output = Source;
+
+    fn index(&self, id: FileId) -> &Self::Output {
+        match self.get(id) {
+            Some(source) => source,
+            None => panic!(
+                "File ID {} is not valid in a source map of length {}",
+                id,
+                self.len()
+            ),
+        }
+    }
+}
+
+impl<'map> IntoIterator for &'map SourceMap {
+    type Item = (FileId, &'map Source);
+    type IntoIter = Sources<'map>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the sources of a [`SourceMap`] and their file IDs.
+/// Double-ended and sized.
+#[derive(Debug, Clone)]
+pub struct Sources<'map> {
+    /// The inner iterator over the sources.
+    inner: Enumerate<slice::Iter<'map, Source>>,
+}
+
+impl<'map> Iterator for Sources<'map> {
+    type Item = (FileId, &'map Source);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(index, source)| (FileId::from_index(index), source))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<'map> DoubleEndedIterator for Sources<'map> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(index, source)| (FileId::from_index(index), source))
+    }
+}
+
+impl<'map> ExactSizeIterator for Sources<'map> {}
@@ -0,0 +1,114 @@
+use super::{FileSpan, SourceMap};
+use crate::Source;
+
+#[test]
+fn find_by_name() {
+    let mut map = SourceMap::new();
+    let foo = map.add("foo.rs", "fn foo() {}");
+    let bar = map.add("bar.rs", "fn bar() {}");
+    assert_ne!(foo, bar);
+    assert_eq!(map.find("foo.rs"), Some(foo));
+    assert_eq!(map.find("bar.rs"), Some(bar));
+    assert_eq!(map.find("baz.rs"), None);
+    assert_eq!(map[bar].contents(), "fn bar() {}");
+    assert_eq!(map[foo].file_id(), Some(foo));
+
+    assert_eq!(map.add("foo.rs", "fn baz() {}"), foo);
+    assert_eq!(map.len(), 2);
+    assert_eq!(map[foo].contents(), "fn foo() {}");
+}
+
+#[test]
+fn global_positions() {
+    let mut map = SourceMap::new();
+    let first = map.add("first.txt", "ab̤̊c");
+    let second = map.add("second.txt", "de\nf");
+
+    let location = map.resolve_location(map.location(first, 3)).unwrap();
+    assert_eq!(map.global_position(&location), Some(3));
+    let (source, resolved) = map.resolve(3).unwrap();
+    assert_eq!(source.file_id(), Some(first));
+    assert_eq!(resolved, location);
+
+    let location = map.resolve_location(map.location(second, 3)).unwrap();
+    assert_eq!(location.file_id(), Some(second));
+    assert_eq!(map.global_position(&location), Some(7));
+    let (source, resolved) = map.resolve(7).unwrap();
+    assert_eq!(source.file_id(), Some(second));
+    assert_eq!(resolved.line_column(), (1, 0));
+
+    assert!(map.resolve(9).is_none());
+}
+
+#[test]
+fn starts_computed_on_demand() {
+    let mut map = SourceMap::new();
+    map.add("first.txt", "ab");
+    map.add("second.txt", "cde");
+    let third = map.add("third.txt", "f\ng");
+    assert!(map.starts[1 ..].iter().all(|start| start.get().is_none()));
+
+    let (source, resolved) = map.resolve(9).unwrap();
+    assert_eq!(source.file_id(), Some(third));
+    assert_eq!(resolved.line_column(), (1, 0));
+    assert_eq!(map.global_position(&resolved), Some(9));
+    assert!(map.resolve(11).is_none());
+
+    let mut map = SourceMap::new();
+    map.add("first.txt", "ab");
+    let second = map.add("second.txt", "cde");
+    map.add("third.txt", "f\ng");
+    let location = map.resolve_location(map.location(second, 1)).unwrap();
+    assert_eq!(map.global_position(&location), Some(4));
+    assert!(map.starts[2].get().is_none());
+    assert_eq!(map.resolve(4).unwrap().1, location);
+}
+
+#[test]
+fn compact_locations() {
+    let mut map = SourceMap::new();
+    map.add("first.txt", "abc");
+    let second = map.add("second.txt", "de\nfg̊h");
+
+    let span = map.full_span(second);
+    assert_eq!(span.len(), 6);
+    let resolved = map.resolve_span(span).unwrap();
+    assert_eq!(resolved.as_str(), "de\nfg̊h");
+    assert_eq!(resolved.file_span(), Some(span));
+
+    let location = map.location(second, 4);
+    let resolved = map.resolve_location(location).unwrap();
+    assert_eq!(resolved.line_column(), (1, 1));
+    assert_eq!(resolved.file_location(), Some(location));
+
+    let tail = map.resolve_span(span).unwrap().try_slice(4 ..).unwrap();
+    assert_eq!(tail.as_str(), "g̊h");
+    let tail = tail.file_span().unwrap();
+    assert_eq!(tail.start(), location);
+    assert_eq!(tail.end(), map.location(second, 6));
+    assert!(map.resolve_span(FileSpan::new(location, 4)).is_none());
+    assert!(Source::new("foo.rs", "abc").full_span().file_span().is_none());
+}
+
+#[test]
+fn foreign_source() {
+    let mut map = SourceMap::new();
+    map.add("foo.rs", "abc");
+    let other = Source::new("foo.rs", "abc");
+    assert!(!map.contains(&other));
+    assert_eq!(map.global_position(&other.full_span().start()), None);
+}
+
+#[test]
+fn load_once() {
+    let path = std::env::temp_dir()
+        .join(format!("nom-grapheme-clusters-map-{}.txt", std::process::id()));
+    std::fs::write(&path, "abc").unwrap();
+    let mut map = SourceMap::new();
+    let first = map.load(&path);
+    std::fs::remove_file(&path).unwrap();
+    let first = first.unwrap();
+    assert_eq!(map.load(&path).unwrap(), first);
+    assert_eq!(map.len(), 1);
+    assert_eq!(map[first].contents(), "abc");
+}
@@ -0,0 +1,114 @@
+//! Exports Unicode normalization of source contents, keeping track of where
+//! normalized contents came from in the original text.
+
+use super::{
+    indexing::{IndexArray, IndexArrayBuilder},
+    Source,
+};
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A Unicode normalization form, see
+/// [UAX #15](https://unicode.org/reports/tr15/).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NormalizationForm {
+    /// Canonical decomposition followed by canonical composition.
+    Nfc,
+    /// Canonical decomposition.
+    Nfd,
+    /// Compatibility decomposition followed by canonical composition.
+    Nfkc,
+    /// Compatibility decomposition.
+    Nfkd,
+}
+
+impl NormalizationForm {
+    /// Normalizes the given text into this form, appending it to `output`.
+    fn normalize_into(self, text: &str, output: &mut String) {
+        match self {
+            Self::Nfc => output.extend(text.nfc()),
+            Self::Nfd => output.extend(text.nfd()),
+            Self::Nfkc => output.extend(text.nfkc()),
+            Self::Nfkd => output.extend(text.nfkd()),
+        }
+    }
+}
+
+/// Original text of normalized contents, with a mapping of byte offsets of the
+/// contents back to the original.
+#[derive(Debug)]
+pub(super) struct Normalization {
+    /// Form the contents were normalized into.
+    form: NormalizationForm,
+    /// Source over the text before normalization.
+    original: Source,
+    /// Byte offsets in the normalized contents where each original grapheme
+    /// cluster starts, followed by the contents' length.
+    normalized_offsets: IndexArray,
+    /// Byte offsets in the original text where each grapheme cluster starts,
+    /// followed by the text's length.
+    original_offsets: IndexArray,
+}
+
+impl Normalization {
+    /// Normalizes the given text, returning the normalized contents and the
+    /// mapping back to the original. Each grapheme cluster is normalized on
+    /// its own, so that every cluster of the original maps to a range of the
+    /// contents.
+    ///
+    /// Extended grapheme clusters are used instead of the segmenter of the
+    /// original source: canonical composition never crosses their
+    /// boundaries, while it may cross those of other segmenters, e.g. `"e"`
+    /// and a combining acute accent are distinct chars but compose into
+    /// `"é"`.
+    pub(super) fn new(
+        original: Source,
+        form: NormalizationForm,
+    ) -> (Box<str>, Self) {
+        let text = original.contents();
+        let mut contents = String::with_capacity(text.len());
+        let mut normalized_offsets = IndexArrayBuilder::new();
+        let mut original_offsets = IndexArrayBuilder::new();
+        for (byte_index, grapheme) in text.grapheme_indices(true) {
+            normalized_offsets.push(contents.len());
+            original_offsets.push(byte_index);
+            form.normalize_into(grapheme, &mut contents);
+        }
+        normalized_offsets.push(contents.len());
+        original_offsets.push(text.len());
+
+        let normalization = Self {
+            form,
+            original,
+            normalized_offsets: normalized_offsets.into(),
+            original_offsets: original_offsets.into(),
+        };
+        (contents.into(), normalization)
+    }
+
+    /// Form the contents were normalized into.
+    pub(super) fn form(&self) -> NormalizationForm {
+        self.form
+    }
+
+    /// Source over the text before normalization.
+    pub(super) fn original(&self) -> &Source {
+        &self.original
+    }
+
+    /// Maps a byte offset of the normalized contents to the original text. An
+    /// offset inside the normalization of an original grapheme cluster maps
+    /// to the start of the cluster, or to its end if `round_up` is set.
+    pub(super) fn original_offset(
+        &self,
+        byte_index: usize,
+        round_up: bool,
+    ) -> usize {
+        match self.normalized_offsets.binary_search(byte_index) {
+            Ok(index) => self.original_offsets.index(index),
+            Err(index) if round_up => self.original_offsets.index(index),
+            Err(index) => self.original_offsets.index(index - 1),
+        }
+    }
+}
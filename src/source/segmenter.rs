@@ -0,0 +1,217 @@
+//! Exports the segmenters splitting the contents of sources into segments.
+
+use std::{fmt, str::CharIndices};
+use unicode_segmentation::{
+    GraphemeIndices,
+    USentenceBoundIndices,
+    UWordBoundIndices,
+    UnicodeSegmentation,
+};
+
+/// Splits text into segments, the elements of spans. A [`Source`] is indexed
+/// by the segmenter set with
+/// [`SourceBuilder::segmenter`](super::SourceBuilder::segmenter), so that
+/// positions, [`LocatedSegment`](crate::LocatedSegment)s and nom parsers work
+/// on its segments, be them grapheme clusters, words or sentences.
+///
+/// Segments must cover the whole text, in order and without overlapping.
+/// Lazily indexed and edited sources segment parts of their contents on their
+/// own, starting at segment boundaries, so segmenting a text from one of its
+/// segment boundaries must yield the same segments as the whole text does
+/// from there.
+///
+/// [`Source`]: super::Source
+pub trait Segmenter: fmt::Debug + Send + Sync {
+    /// Splits the given text into segments, yielding the byte index and the
+    /// contents of each segment.
+    fn segment_indices<'text>(&self, text: &'text str)
+        -> SegmentIndices<'text>;
+
+    /// Counts the segments of the given text.
+    fn count(&self, text: &str) -> usize {
+        self.segment_indices(text).count()
+    }
+
+    /// Number of segments at the end of a text that appending more text can
+    /// change, e.g. by merging them with the appended text. Streams hold
+    /// these segments back until closed (see
+    /// [`StreamingSource`](crate::StreamingSource)). Defaults to one, the
+    /// last segment.
+    fn unstable_segments(&self) -> usize {
+        1
+    }
+
+    /// The built-in segmentation this segmenter is, if any. Custom segmenters
+    /// need not override it.
+    fn segmentation(&self) -> Option<Segmentation> {
+        None
+    }
+}
+
+/// The built-in segmenters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Segmentation {
+    /// Extended grapheme clusters, as defined by
+    /// [UAX #29](https://unicode.org/reports/tr29/).
+    #[default]
+    Extended,
+    /// Legacy grapheme clusters, which, unlike extended ones, do not include
+    /// spacing marks and prepend characters.
+    Legacy,
+    /// Unicode scalar values (Rust `char`s). Faster to segment, but combining
+    /// marks become segments of their own, as do carriage returns followed by
+    /// linefeeds.
+    Chars,
+    /// Words, as delimited by the word boundaries of
+    /// [UAX #29](https://unicode.org/reports/tr29/). Runs of whitespace,
+    /// punctuation and line breaks become segments of their own, e.g.
+    /// `"Hello, world"` is split into `"Hello"`, `","`, `" "` and `"world"`.
+    Words,
+    /// Sentences, as delimited by the sentence boundaries of
+    /// [UAX #29](https://unicode.org/reports/tr29/). A sentence includes its
+    /// trailing whitespace, and a sentence ending with a line break ends a
+    /// line.
+    Sentences,
+}
+
+impl Segmenter for Segmentation {
+    fn segment_indices<'text>(
+        &self,
+        text: &'text str,
+    ) -> SegmentIndices<'text> {
+        let inner = match self {
+            Self::Extended => {
+                SegmentIndicesInner::Graphemes(text.grapheme_indices(true))
+            },
+            Self::Legacy => {
+                SegmentIndicesInner::Graphemes(text.grapheme_indices(false))
+            },
+            Self::Chars => SegmentIndicesInner::Chars(text.char_indices()),
+            Self::Words => {
+                SegmentIndicesInner::Words(text.split_word_bound_indices())
+            },
+            Self::Sentences => SegmentIndicesInner::Sentences(
+                text.split_sentence_bound_indices(),
+            ),
+        };
+        SegmentIndices { text, inner }
+    }
+
+    fn count(&self, text: &str) -> usize {
+        match self {
+            Self::Chars => text.chars().count(),
+            _ => self.segment_indices(text).count(),
+        }
+    }
+
+    fn unstable_segments(&self) -> usize {
+        match self {
+            // Word and sentence boundaries look past the next segment, e.g.
+            // `"a:"` is split into two words, but `"a:b"` is a single one.
+            Self::Words | Self::Sentences => 2,
+            Self::Extended | Self::Legacy | Self::Chars => 1,
+        }
+    }
+
+    fn segmentation(&self) -> Option<Segmentation> {
+        Some(*self)
+    }
+}
+
+/// Iterator over the segments of a text, yielding the byte index and the
+/// contents of each segment. Created by [`Segmenter::segment_indices`].
+#[derive(Debug, Clone)]
+pub struct SegmentIndices<'text> {
+    /// The text being segmented.
+    text: &'text str,
+    /// Iterator of the segmenter in use.
+    inner: SegmentIndicesInner<'text>,
+}
+
+impl<'text> SegmentIndices<'text> {
+    /// Creates segment indices of the given text from an iterator yielding
+    /// the byte index and the contents of each segment, allowing custom
+    /// [`Segmenter`]s. The iterator must be clonable so that segment indices
+    /// are, e.g. to search a text for the segments of another.
+    pub fn new<I>(text: &'text str, iter: I) -> Self
+    where
+        I: Iterator<Item = (usize, &'text str)> + Clone + 'text,
+    {
+        Self { text, inner: SegmentIndicesInner::Custom(Box::new(iter)) }
+    }
+}
+
+/// Iterator of a [`SegmentIndices`], depending on the segmenter.
+enum SegmentIndicesInner<'text> {
+    /// Extended or legacy grapheme clusters.
+    Graphemes(GraphemeIndices<'text>),
+    /// Unicode scalar values.
+    Chars(CharIndices<'text>),
+    /// Words.
+    Words(UWordBoundIndices<'text>),
+    /// Sentences.
+    Sentences(USentenceBoundIndices<'text>),
+    /// A custom segmenter.
+    Custom(Box<dyn CustomIndices<'text> + 'text>),
+}
+
+impl<'text> Clone for SegmentIndicesInner<'text> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Graphemes(iter) => Self::Graphemes(iter.clone()),
+            Self::Chars(iter) => Self::Chars(iter.clone()),
+            Self::Words(iter) => Self::Words(iter.clone()),
+            Self::Sentences(iter) => Self::Sentences(iter.clone()),
+            Self::Custom(iter) => Self::Custom(iter.clone_boxed()),
+        }
+    }
+}
+
+/// Iterator of a custom [`Segmenter`], which can be cloned while boxed.
+trait CustomIndices<'text>: Iterator<Item = (usize, &'text str)> {
+    /// Clones this iterator into a new box.
+    fn clone_boxed(&self) -> Box<dyn CustomIndices<'text> + 'text>;
+}
+
+impl<'text, I> CustomIndices<'text> for I
+where
+    I: Iterator<Item = (usize, &'text str)> + Clone + 'text,
+{
+    fn clone_boxed(&self) -> Box<dyn CustomIndices<'text> + 'text> {
+        Box::new(self.clone())
+    }
+}
+
+impl<'text> fmt::Debug for SegmentIndicesInner<'text> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Graphemes(iter) => {
+                fmtr.debug_tuple("Graphemes").field(iter).finish()
+            },
+            Self::Chars(iter) => fmtr.debug_tuple("Chars").field(iter).finish(),
+            Self::Words(iter) => fmtr.debug_tuple("Words").field(iter).finish(),
+            Self::Sentences(iter) => {
+                fmtr.debug_tuple("Sentences").field(iter).finish()
+            },
+            Self::Custom(_) => fmtr.write_str("Custom(..)"),
+        }
+    }
+}
+
+impl<'text> Iterator for SegmentIndices<'text> {
+    type Item = (usize, &'text str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.inner {
+            SegmentIndicesInner::Graphemes(iter) => iter.next(),
+            SegmentIndicesInner::Chars(iter) => {
+                let (index, ch) = iter.next()?;
+                Some((index, &self.text[index .. index + ch.len_utf8()]))
+            },
+            SegmentIndicesInner::Words(iter) => iter.next(),
+            SegmentIndicesInner::Sentences(iter) => iter.next(),
+            SegmentIndicesInner::Custom(iter) => iter.next(),
+        }
+    }
+}
@@ -0,0 +1,440 @@
+//! Exports the tables indexing segments and line breaks of a source, either
+//! built eagerly or lazily in chunks.
+
+use super::{
+    indexing::{
+        IndexArray,
+        IndexArrayBuilder,
+        SplicedIndexArray,
+        SplicedIndexArrayBuilder,
+    },
+    LineBreakPolicy,
+    Segmenter,
+};
+use std::{
+    ops::Range,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        OnceLock,
+    },
+};
+
+/// Table of the segments and line breaks of a source. Every operation takes
+/// the source's contents, which lazy tables segment on demand.
+#[derive(Debug)]
+pub(super) enum SegmentTable {
+    /// Whole contents indexed upfront.
+    Eager {
+        /// Byte index per segment, followed by the contents' length.
+        segments: Arc<IndexArray>,
+        /// Segment positions of line breaks.
+        newlines: Arc<IndexArray>,
+    },
+    /// Contents indexed in chunks on first access.
+    Lazy(LazyTable),
+    /// Contents indexed by slices of the tables of other sources, such as an
+    /// edited source sharing the indices of the regions left untouched.
+    Spliced {
+        /// Byte index per segment, followed by the contents' length.
+        segments: SplicedIndexArray,
+        /// Segment positions of line breaks.
+        newlines: SplicedIndexArray,
+    },
+}
+
+impl SegmentTable {
+    /// Creates a lazy table for contents of the given length in bytes.
+    pub(super) fn lazy(
+        text_len: usize,
+        chunk_size: usize,
+        segmenter: Arc<dyn Segmenter>,
+        line_break_policy: LineBreakPolicy,
+    ) -> Self {
+        let chunk_count = text_len.div_ceil(chunk_size);
+        Self::Lazy(LazyTable {
+            chunk_size,
+            segmenter,
+            line_break_policy,
+            chunks: (0 .. chunk_count).map(|_| OnceLock::new()).collect(),
+            ready: AtomicUsize::new(0),
+        })
+    }
+
+    /// Number of segments.
+    pub(super) fn len(&self, text: &str) -> usize {
+        match self {
+            Self::Eager { segments, .. } => segments.len() - 1,
+            Self::Spliced { segments, .. } => segments.len() - 1,
+            Self::Lazy(table) => table.len(text),
+        }
+    }
+
+    /// Number of line breaks.
+    pub(super) fn newline_count(&self, text: &str) -> usize {
+        match self {
+            Self::Eager { newlines, .. } => newlines.len(),
+            Self::Spliced { newlines, .. } => newlines.len(),
+            Self::Lazy(table) => table
+                .force_all(text)
+                .map_or(0, |chunk| chunk.start_line + chunk.newlines.len()),
+        }
+    }
+
+    /// Byte index where the segment at the given position starts. The
+    /// position right after the last segment yields the contents' length.
+    pub(super) fn byte_index(
+        &self,
+        text: &str,
+        position: usize,
+    ) -> Option<usize> {
+        match self {
+            Self::Eager { segments, .. } => segments.get(position),
+            Self::Spliced { segments, .. } => segments.get(position),
+            Self::Lazy(table) => {
+                match table.find(text, |chunk| chunk.end_position() <= position)
+                {
+                    Some(chunk) => Some(
+                        chunk.start_byte
+                            + chunk
+                                .segments
+                                .index(position - chunk.start_position),
+                    ),
+                    None if position == table.len(text) => Some(text.len()),
+                    None => None,
+                }
+            },
+        }
+    }
+
+    /// Searches the position of the segment starting at the given byte index,
+    /// as a binary search over the segments' byte indices followed by the
+    /// contents' length.
+    pub(super) fn search_byte(
+        &self,
+        text: &str,
+        byte_index: usize,
+    ) -> Result<usize, usize> {
+        match self {
+            Self::Eager { segments, .. } => segments.binary_search(byte_index),
+            Self::Spliced { segments, .. } => {
+                segments.binary_search(byte_index)
+            },
+            Self::Lazy(table) => {
+                if byte_index >= text.len() {
+                    let len = table.len(text);
+                    return if byte_index == text.len() {
+                        Ok(len)
+                    } else {
+                        Err(len + 1)
+                    };
+                }
+                // A segment starting in the nth chunk of bytes is always
+                // indexed by the nth chunk.
+                let chunk = table.force(text, byte_index / table.chunk_size);
+                if byte_index < chunk.start_byte {
+                    return Err(chunk.start_position);
+                }
+                match chunk
+                    .segments
+                    .binary_search(byte_index - chunk.start_byte)
+                {
+                    Ok(index) => Ok(chunk.start_position + index),
+                    Err(index) => Err(chunk.start_position + index),
+                }
+            },
+        }
+    }
+
+    /// Position of the nth line break.
+    pub(super) fn newline(&self, text: &str, index: usize) -> Option<usize> {
+        match self {
+            Self::Eager { newlines, .. } => newlines.get(index),
+            Self::Spliced { newlines, .. } => newlines.get(index),
+            Self::Lazy(table) => {
+                let chunk = table.find(text, |chunk| {
+                    chunk.start_line + chunk.newlines.len() <= index
+                })?;
+                Some(
+                    chunk.start_position
+                        + chunk.newlines.index(index - chunk.start_line),
+                )
+            },
+        }
+    }
+
+    /// Searches the given position among the positions of line breaks, as a
+    /// binary search.
+    pub(super) fn search_newline(
+        &self,
+        text: &str,
+        position: usize,
+    ) -> Result<usize, usize> {
+        match self {
+            Self::Eager { newlines, .. } => newlines.binary_search(position),
+            Self::Spliced { newlines, .. } => newlines.binary_search(position),
+            Self::Lazy(table) => {
+                match table.find(text, |chunk| chunk.end_position() <= position)
+                {
+                    Some(chunk) => match chunk
+                        .newlines
+                        .binary_search(position - chunk.start_position)
+                    {
+                        Ok(index) => Ok(chunk.start_line + index),
+                        Err(index) => Err(chunk.start_line + index),
+                    },
+                    None => Err(self.newline_count(text)),
+                }
+            },
+        }
+    }
+
+    /// Pushes the byte indices of the segments in the given range of
+    /// positions and the positions of the line breaks among them onto the
+    /// given builders, shifted by the given amounts. The indices are shared
+    /// with this table rather than copied.
+    pub(super) fn slice_into(
+        &self,
+        text: &str,
+        positions: Range<usize>,
+        byte_shift: isize,
+        position_shift: isize,
+        segments: &mut SplicedIndexArrayBuilder,
+        newlines: &mut SplicedIndexArrayBuilder,
+    ) {
+        match self {
+            Self::Eager {
+                segments: table_segments,
+                newlines: table_newlines,
+            } => {
+                let newline_range =
+                    search_index(table_newlines.binary_search(positions.start))
+                        .. search_index(
+                            table_newlines.binary_search(positions.end),
+                        );
+                segments.push_shared(table_segments, positions, byte_shift);
+                newlines
+                    .push_shared(table_newlines, newline_range, position_shift);
+            },
+            Self::Lazy(table) => table.slice_into(
+                text, positions, byte_shift, position_shift, segments, newlines,
+            ),
+            Self::Spliced {
+                segments: table_segments,
+                newlines: table_newlines,
+            } => {
+                let newline_range =
+                    search_index(table_newlines.binary_search(positions.start))
+                        .. search_index(
+                            table_newlines.binary_search(positions.end),
+                        );
+                table_segments.slice_into(positions, byte_shift, segments);
+                table_newlines
+                    .slice_into(newline_range, position_shift, newlines);
+            },
+        }
+    }
+
+    /// Number of chunks indexed so far, or `None` for eager tables.
+    #[cfg(test)]
+    pub(super) fn indexed_chunks(&self) -> Option<usize> {
+        match self {
+            Self::Eager { .. } | Self::Spliced { .. } => None,
+            Self::Lazy(table) => Some(table.ready.load(Ordering::Acquire)),
+        }
+    }
+}
+
+/// Table indexed in chunks of bytes. The nth chunk indexes every segment
+/// starting in the nth `chunk_size` bytes of the contents. Chunks are indexed
+/// in order, since positions and lines depend on the previous chunks.
+#[derive(Debug)]
+pub(super) struct LazyTable {
+    /// Size of a chunk in bytes.
+    chunk_size: usize,
+    /// How contents are split into segments.
+    segmenter: Arc<dyn Segmenter>,
+    /// Policy used to find line breaks.
+    line_break_policy: LineBreakPolicy,
+    /// The chunks, indexed on demand.
+    chunks: Box<[OnceLock<Chunk>]>,
+    /// Length of the prefix of chunks already indexed.
+    ready: AtomicUsize,
+}
+
+impl LazyTable {
+    /// Number of segments, indexing every chunk.
+    fn len(&self, text: &str) -> usize {
+        self.force_all(text).map_or(0, Chunk::end_position)
+    }
+
+    /// Indexes every chunk, returning the last one.
+    fn force_all(&self, text: &str) -> Option<&Chunk> {
+        let count = self.chunks.len();
+        (count > 0).then(|| self.force(text, count - 1))
+    }
+
+    /// Indexes every chunk up to the given one, returning it.
+    fn force(&self, text: &str, index: usize) -> &Chunk {
+        let mut ready = self.ready.load(Ordering::Acquire);
+        while ready <= index {
+            let (start_byte, start_position, start_line) = match ready {
+                0 => (0, 0, 0),
+                _ => {
+                    let prev = self.ready_chunk(ready - 1);
+                    (
+                        prev.end_byte,
+                        prev.end_position(),
+                        prev.start_line + prev.newlines.len(),
+                    )
+                },
+            };
+            self.chunks[ready].get_or_init(|| {
+                let end = ((ready + 1) * self.chunk_size).min(text.len());
+                Chunk::index(
+                    text,
+                    start_byte .. end,
+                    start_position,
+                    start_line,
+                    &*self.segmenter,
+                    self.line_break_policy,
+                )
+            });
+            ready += 1;
+            self.ready.fetch_max(ready, Ordering::AcqRel);
+        }
+        self.ready_chunk(index)
+    }
+
+    /// Gets a chunk known to be indexed.
+    fn ready_chunk(&self, index: usize) -> &Chunk {
+        match self.chunks[index].get() {
+            Some(chunk) => chunk,
+            None => unreachable!("Chunk {} must be indexed", index),
+        }
+    }
+
+    /// Pushes the indices of the given range of positions onto the given
+    /// builders, as in [`SegmentTable::slice_into`], indexing chunks as
+    /// needed.
+    fn slice_into(
+        &self,
+        text: &str,
+        positions: Range<usize>,
+        byte_shift: isize,
+        position_shift: isize,
+        segments: &mut SplicedIndexArrayBuilder,
+        newlines: &mut SplicedIndexArrayBuilder,
+    ) {
+        for index in 0 .. self.chunks.len() {
+            let chunk = self.force(text, index);
+            if chunk.start_position >= positions.end {
+                break;
+            }
+            let start = positions.start.max(chunk.start_position)
+                - chunk.start_position;
+            let end =
+                positions.end.min(chunk.end_position()) - chunk.start_position;
+            if start >= end {
+                continue;
+            }
+            let newline_range =
+                search_index(chunk.newlines.binary_search(start))
+                    .. search_index(chunk.newlines.binary_search(end));
+            segments.push_shared(
+                &chunk.segments,
+                start .. end,
+                byte_shift + chunk.start_byte as isize,
+            );
+            newlines.push_shared(
+                &chunk.newlines,
+                newline_range,
+                position_shift + chunk.start_position as isize,
+            );
+        }
+    }
+
+    /// Finds the first chunk not lying entirely before some target, indexing
+    /// chunks as needed. `before` must be monotonic over the chunks.
+    fn find<F>(&self, text: &str, before: F) -> Option<&Chunk>
+    where
+        F: Fn(&Chunk) -> bool,
+    {
+        let ready = self.ready.load(Ordering::Acquire);
+        let index = self.chunks[.. ready]
+            .partition_point(|chunk| chunk.get().is_some_and(&before));
+        if index < ready {
+            return Some(self.ready_chunk(index));
+        }
+        (ready .. self.chunks.len())
+            .map(|index| self.force(text, index))
+            .find(|chunk| !before(chunk))
+    }
+}
+
+/// A chunk of a lazy table.
+#[derive(Debug)]
+struct Chunk {
+    /// Byte index of the first segment.
+    start_byte: usize,
+    /// Byte index where the next chunk starts.
+    end_byte: usize,
+    /// Position of the first segment.
+    start_position: usize,
+    /// Number of line breaks before this chunk.
+    start_line: usize,
+    /// Byte index per segment, relative to `start_byte`.
+    segments: Arc<IndexArray>,
+    /// Positions of line breaks, relative to `start_position`.
+    newlines: Arc<IndexArray>,
+}
+
+impl Chunk {
+    /// Indexes the segments starting in the given range of bytes. The range
+    /// must start at a segment boundary.
+    fn index(
+        text: &str,
+        bytes: Range<usize>,
+        start_position: usize,
+        start_line: usize,
+        segmenter: &dyn Segmenter,
+        line_break_policy: LineBreakPolicy,
+    ) -> Self {
+        let mut segments = IndexArrayBuilder::new();
+        let mut newlines = IndexArrayBuilder::new();
+        let mut end_byte = text.len();
+        let text_from = &text[bytes.start ..];
+        for (index, grapheme) in segmenter.segment_indices(text_from) {
+            if bytes.start + index >= bytes.end {
+                end_byte = bytes.start + index;
+                break;
+            }
+            let following = &text_from[index + grapheme.len() ..];
+            if line_break_policy.ends_line(grapheme, following) {
+                newlines.push(segments.len());
+            }
+            segments.push(index);
+        }
+        Self {
+            start_byte: bytes.start,
+            end_byte,
+            start_position,
+            start_line,
+            segments: Arc::new(segments.finish()),
+            newlines: Arc::new(newlines.finish()),
+        }
+    }
+
+    /// Position right after the last segment.
+    fn end_position(&self) -> usize {
+        self.start_position + self.segments.len()
+    }
+}
+
+/// The meta-index yielded by a binary search, whether the element was found
+/// or not, i.e. the number of elements smaller than it.
+fn search_index(result: Result<usize, usize>) -> usize {
+    match result {
+        Ok(index) | Err(index) => index,
+    }
+}
@@ -1,4 +1,18 @@
-use super::Source;
+use super::{
+    CaseFolding,
+    LineBreakPolicy,
+    LineColumnError,
+    LoadError,
+    NormalizationForm,
+    SegmentIndices,
+    Segmentation,
+    Segmenter,
+    Source,
+    SourceBuilder,
+    TextEdit,
+    Utf8Policy,
+};
+use crate::{Location, Span};
 
 #[test]
 fn index_segments() {
@@ -33,3 +47,513 @@ fn index_segments() {
     index += 1;
     assert_eq!(source.get(index), None);
 }
+
+#[test]
+fn crlf_line_breaks() {
+    let source = Source::new("windows.txt", "ab\r\ncd\r\n\r\ne");
+    let segments: Vec<_> = source.full_span().segments().collect();
+    assert_eq!(segments[2].as_str(), "\r\n");
+    assert!(segments[2].is_line_break());
+    assert_eq!(segments[1].location().line_column(), (0, 1));
+    assert_eq!(segments[3].location().line_column(), (1, 0));
+    assert_eq!(segments[4].location().line_column(), (1, 1));
+    assert_eq!(segments[6].location().line_column(), (2, 0));
+    assert_eq!(segments[7].location().line_column(), (3, 0));
+    assert_eq!(segments[3].location().line_span().as_str(), "cd\r\n");
+}
+
+#[test]
+fn unicode_line_breaks() {
+    let source = Source::new("unicode.txt", "a\rb\u{85}c\u{2028}d\u{2029}e");
+    let lines: Vec<_> = source
+        .full_span()
+        .segments()
+        .map(|segment| segment.location().line())
+        .collect();
+    assert_eq!(lines, [0, 0, 1, 1, 2, 2, 3, 3, 4]);
+}
+
+#[test]
+fn line_break_policy() {
+    let contents = "a\rb\r\nc\u{2028}d";
+
+    let source = SourceBuilder::new()
+        .line_break_policy(LineBreakPolicy::LineFeed)
+        .finish("lf.txt", contents);
+    let end = source.full_span().end();
+    assert_eq!(end.line_column(), (1, 3));
+
+    let source = SourceBuilder::new()
+        .line_break_policy(LineBreakPolicy::Ascii)
+        .finish("ascii.txt", contents);
+    let end = source.full_span().end();
+    assert_eq!(end.line_column(), (2, 3));
+
+    let source = Source::new("unicode.txt", contents);
+    assert_eq!(source.line_break_policy(), LineBreakPolicy::Unicode);
+    let end = source.full_span().end();
+    assert_eq!(end.line_column(), (3, 1));
+}
+
+#[test]
+fn case_folding() {
+    let folding = CaseFolding::Default;
+    assert!(folding.eq_ignore_case("Straße", "STRASSE"));
+    assert!(folding.eq_ignore_case("ß", "ẞ"));
+    assert!(folding.eq_ignore_case("ΟΔΟΣ", "οδος"));
+    assert!(folding.eq_ignore_case("ς", "Σ"));
+    assert!(folding.eq_ignore_case("\u{212A}", "k"));
+    assert!(folding.eq_ignore_case("İ", "i\u{307}"));
+    assert!(!folding.eq_ignore_case("ı", "i"));
+    assert!(!folding.eq_ignore_case("ı", "I"));
+    assert_eq!(folding.fold("Maße"), "masse");
+
+    let folding = CaseFolding::Turkic;
+    assert!(folding.eq_ignore_case("ı", "I"));
+    assert!(folding.eq_ignore_case("i", "İ"));
+    assert!(!folding.eq_ignore_case("i", "I"));
+    assert!(folding.eq_ignore_case("ß", "ss"));
+
+    let source = SourceBuilder::new()
+        .case_folding(CaseFolding::Turkic)
+        .finish("tr.txt", "İstanbul");
+    assert_eq!(source.case_folding(), CaseFolding::Turkic);
+    assert_eq!(Source::new("a.txt", "").case_folding(), CaseFolding::Default);
+}
+
+#[test]
+fn visual_columns() {
+    let source = Source::new("tabs.py", "a\tb\n\t\t日x\n");
+    let segments: Vec<_> = source.full_span().segments().collect();
+    let tab_width = 4;
+    assert_eq!(segments[0].location().visual_column(tab_width), 0);
+    assert_eq!(segments[1].location().visual_column(tab_width), 1);
+    assert_eq!(segments[2].location().visual_column(tab_width), 4);
+    assert_eq!(segments[3].location().visual_column(tab_width), 5);
+    assert_eq!(segments[5].location().visual_column(tab_width), 4);
+    assert_eq!(segments[6].location().visual_column(tab_width), 8);
+    assert_eq!(segments[7].location().visual_column(tab_width), 10);
+    assert_eq!(segments[7].location().visual_column(2), 6);
+
+    let at = |line, column| {
+        source
+            .location_at_visual_column(line, column, tab_width)
+            .map(|location| location.position())
+    };
+    assert_eq!(at(0, 0), Some(0));
+    assert_eq!(at(0, 1), Some(1));
+    assert_eq!(at(0, 3), Some(1));
+    assert_eq!(at(0, 4), Some(2));
+    assert_eq!(at(0, 5), Some(3));
+    assert_eq!(at(0, 6), None);
+    assert_eq!(at(1, 7), Some(5));
+    assert_eq!(at(1, 9), Some(6));
+    assert_eq!(at(1, 10), Some(7));
+    assert_eq!(at(1, 11), Some(8));
+    assert_eq!(at(2, 0), Some(9));
+    assert_eq!(at(3, 0), None);
+}
+
+#[test]
+fn byte_offsets() {
+    let source = Source::new("bytes.txt", "av́e\nmař̋ia");
+    let span = source.full_span().try_slice(1 .. 6).unwrap();
+    assert_eq!(span.as_str(), "v́e\nma");
+    assert_eq!(span.start().byte_offset(), 1);
+    assert_eq!(span.byte_range(), 1 .. 8);
+    assert_eq!(&source.contents()[span.byte_range()], span.as_str());
+
+    assert_eq!(source.location_at_byte(0).unwrap().position(), 0);
+    assert_eq!(source.location_at_byte(4).unwrap().position(), 2);
+    assert_eq!(source.location_at_byte(8).unwrap().as_str(), "ř̋");
+    assert_eq!(source.location_at_byte(14).unwrap(), source.full_span().end());
+
+    let error = source.location_at_byte(2).unwrap_err();
+    assert_eq!(error.byte_offset(), 2);
+    assert_eq!(error.containing().unwrap().as_str(), "v́");
+    let error = source.location_at_byte(10).unwrap_err();
+    assert_eq!(error.containing().unwrap().position(), 6);
+    let error = source.location_at_byte(15).unwrap_err();
+    assert_eq!(error.containing(), None);
+}
+
+#[test]
+fn line_column_to_location() {
+    let source = Source::new("complicated.rs", "av́e\nmař̋ia\n");
+
+    let location = source.location_at(1, 2).unwrap();
+    assert_eq!(location.as_str(), "ř̋");
+    assert_eq!(location.line_column(), (1, 2));
+    assert_eq!(source.location_at(0, 3).unwrap().as_str(), "\n");
+    assert_eq!(source.location_at(2, 0).unwrap(), source.full_span().end());
+
+    assert_eq!(
+        source.location_at(0, 4),
+        Err(LineColumnError::ColumnOutOfBounds {
+            line: 0,
+            column: 4,
+            line_len: 3
+        })
+    );
+    assert_eq!(
+        source.location_at(3, 0),
+        Err(LineColumnError::LineOutOfBounds { line: 3, line_count: 3 })
+    );
+
+    assert_eq!(source.location_at_clamped(0, 10).position(), 3);
+    assert_eq!(source.location_at_clamped(1, 1).position(), 5);
+    assert_eq!(source.location_at_clamped(7, 7), source.full_span().end());
+}
+
+#[test]
+fn lines() {
+    let source = Source::new("lines.txt", "av́e\r\n\nmař̋ia\n");
+    assert_eq!(source.line_count(), 4);
+    assert_eq!(source.line(0).unwrap().as_str(), "av́e\r\n");
+    assert_eq!(source.line_content(0).unwrap().as_str(), "av́e");
+    assert_eq!(source.line(1).unwrap().as_str(), "\n");
+    assert_eq!(source.line_content(1).unwrap().as_str(), "");
+    assert_eq!(source.line(2).unwrap().as_str(), "mař̋ia\n");
+    assert_eq!(source.line(3).unwrap().as_str(), "");
+    assert_eq!(source.line(3).unwrap().start(), source.full_span().end());
+    assert_eq!(source.line(4), None);
+    assert_eq!(source.line_content(4), None);
+
+    let lines: Vec<_> = source.lines().map(|span| span.content()).collect();
+    assert_eq!(lines, ["av́e\r\n", "\n", "mař̋ia\n", ""]);
+    let lines: Vec<_> =
+        source.lines().rev().map(|span| span.content()).collect();
+    assert_eq!(lines, ["", "mař̋ia\n", "\n", "av́e\r\n"]);
+    assert_eq!(source.lines().len(), 4);
+
+    let newlines: Vec<_> = source.newline_indices().collect();
+    assert_eq!(newlines, [3, 4, 10]);
+}
+
+#[test]
+fn load_from_reader() {
+    let bytes: &[u8] = b"\xEF\xBB\xBFfn main() {}\n";
+    let source = Source::from_reader("main.rs", bytes).unwrap();
+    assert_eq!(source.name(), "main.rs");
+    assert_eq!(source.path(), None);
+    assert_eq!(source.contents(), "fn main() {}\n");
+    assert_eq!(source.invalid_utf8().len(), 0);
+}
+
+#[test]
+fn load_invalid_utf8() {
+    let bytes: &[u8] = b"ab\xFFc\n\xE2\x82d\xCC\x81";
+    let error = match Source::from_reader("bad.txt", bytes) {
+        Err(LoadError::InvalidUtf8(error)) => error,
+        other => panic!("expected invalid UTF-8, got {:?}", other),
+    };
+    let locations: Vec<_> =
+        error.locations().map(|location| location.line_column()).collect();
+    assert_eq!(locations, [(0, 2), (1, 0)]);
+    assert_eq!(
+        error.decoded_source().contents(),
+        "ab\u{FFFD}c\n\u{FFFD}d\u{301}"
+    );
+
+    let source = SourceBuilder::new()
+        .utf8_policy(Utf8Policy::Lossy)
+        .from_reader("bad.txt", bytes)
+        .unwrap();
+    let positions: Vec<_> =
+        source.invalid_utf8().map(|location| location.position()).collect();
+    assert_eq!(positions, [2, 5]);
+
+    let (edited, _) =
+        source.apply_edits(&[TextEdit::new(0 .. 1, "xyz")]).unwrap();
+    let positions: Vec<_> =
+        edited.invalid_utf8().map(|location| location.position()).collect();
+    assert_eq!(positions, [4, 7]);
+    let (edited, _) = edited.apply_edits(&[TextEdit::new(4 .. 5, "")]).unwrap();
+    let positions: Vec<_> =
+        edited.invalid_utf8().map(|location| location.position()).collect();
+    assert_eq!(positions, [6]);
+}
+
+#[test]
+fn load_from_path() {
+    let path = std::env::temp_dir()
+        .join(format!("nom-grapheme-clusters-{}.txt", std::process::id()));
+    std::fs::write(&path, "\u{FEFF}line\r\nline").unwrap();
+    let source = Source::from_path(&path);
+    std::fs::remove_file(&path).unwrap();
+    let source = source.unwrap();
+    assert_eq!(source.path(), Some(path.as_path()));
+    assert_eq!(source.name(), path.display().to_string());
+    assert_eq!(source.contents(), "line\r\nline");
+    assert_eq!(source.line_count(), 2);
+
+    assert!(matches!(Source::from_path(&path), Err(LoadError::Io(_))));
+}
+
+#[test]
+fn lazy_indexing() {
+    let contents = "ab\r\n👨‍👩‍👧🇧🇷🇦🇷\u{2028}x̂y\n\n\r\r\nz̃ñ̃\u{301}";
+    let eager = Source::new("lazy.txt", contents);
+    for chunk_size in 1 ..= contents.len() + 1 {
+        let lazy = SourceBuilder::new()
+            .chunk_size(chunk_size)
+            .finish_lazy("lazy.txt", contents);
+        assert_eq!(
+            lazy.seg_byte_indices().collect::<Vec<_>>(),
+            eager.seg_byte_indices().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            lazy.newline_indices().collect::<Vec<_>>(),
+            eager.newline_indices().collect::<Vec<_>>()
+        );
+        for position in 0 ..= eager.len() {
+            assert_eq!(
+                Location::new(lazy.clone(), position).line_column(),
+                Location::new(eager.clone(), position).line_column()
+            );
+        }
+        let byte_position = |source: &Source, byte_offset| {
+            source
+                .location_at_byte(byte_offset)
+                .map(|location| location.position())
+                .map_err(|error| error.containing().map(Location::position))
+        };
+        for byte_offset in 0 ..= contents.len() + 1 {
+            assert_eq!(
+                byte_position(&lazy, byte_offset),
+                byte_position(&eager, byte_offset)
+            );
+        }
+    }
+}
+
+#[test]
+fn lazy_indexing_head() {
+    let contents = "line\n".repeat(1000);
+    let source =
+        SourceBuilder::new().chunk_size(64).finish_lazy("head.txt", contents);
+    assert_eq!(source.indexed_chunks(), Some(0));
+    let location = source.location_at_byte(20).unwrap();
+    assert_eq!(location.line_column(), (4, 0));
+    assert_eq!(source.indexed_chunks(), Some(1));
+    let span = Span::new(location, 100);
+    assert_eq!(span.end().line_column(), (24, 0));
+    assert_eq!(source.indexed_chunks(), Some(2));
+    assert_eq!(source.line_count(), 1001);
+    assert_eq!(source.indexed_chunks(), Some(5000 / 64 + 1));
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn memory_mapped() {
+    let path = std::env::temp_dir()
+        .join(format!("nom-grapheme-clusters-mmap-{}.txt", std::process::id()));
+    std::fs::write(&path, "\u{FEFF}ab\r\ncd").unwrap();
+    let source = unsafe { Source::map_path(&path) }.unwrap();
+    assert_eq!(source.path(), Some(path.as_path()));
+    assert_eq!(source.contents(), "ab\r\ncd");
+    assert_eq!(source.location_at(1, 1).unwrap().as_str(), "d");
+    drop(source);
+
+    std::fs::write(&path, b"ab\xFF").unwrap();
+    let result = unsafe { Source::map_path(&path) };
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(result, Err(LoadError::InvalidUtf8(_))));
+}
+
+#[test]
+fn normalized_source() {
+    let nfc = Source::new_normalized(
+        "nfc.txt",
+        "caf\u{e9} \u{fb03}",
+        NormalizationForm::Nfd,
+    );
+    let nfd = Source::new("nfd.txt", "cafe\u{301} \u{fb03}");
+    assert_eq!(nfc.contents(), nfd.contents());
+    assert_eq!(nfc.normalization_form(), Some(NormalizationForm::Nfd));
+    assert_eq!(nfd.normalization_form(), None);
+    assert_eq!(nfc.original_contents(), "caf\u{e9} \u{fb03}");
+    assert_eq!(nfd.original_contents(), nfd.contents());
+
+    let span = nfc.full_span();
+    let e = span.try_slice(3 .. 4).unwrap();
+    assert_eq!(e.byte_range(), 3 .. 6);
+    assert_eq!(e.original_byte_range(), 3 .. 5);
+    assert_eq!(span.try_slice(4 ..).unwrap().start().original_byte_offset(), 5);
+    assert_eq!(span.original_byte_range(), 0 .. 9);
+
+    let nfkc = Source::new_normalized(
+        "nfkc.txt",
+        "\u{fb03}x",
+        NormalizationForm::Nfkc,
+    );
+    assert_eq!(nfkc.contents(), "ffix");
+    let span = nfkc.full_span();
+    assert_eq!(span.try_slice(1 .. 2).unwrap().original_byte_range(), 0 .. 3);
+    assert_eq!(span.try_slice(1 ..).unwrap().start().original_byte_offset(), 0);
+    assert_eq!(span.try_slice(3 ..).unwrap().start().original_byte_offset(), 3);
+    assert_eq!(Location::new(nfkc.clone(), 2).original_position(), 0);
+    assert_eq!(Location::new(nfkc.clone(), 3).original_position(), 1);
+
+    let lines = Source::new_normalized(
+        "lines.txt",
+        "\u{fb03}\nx\u{fb03}y",
+        NormalizationForm::Nfkc,
+    );
+    assert_eq!(lines.contents(), "ffi\nxffiy");
+    assert_eq!(lines.original_source().contents(), "\u{fb03}\nx\u{fb03}y");
+    assert_eq!(lines.original_source().name(), "lines.txt");
+    let y = Location::new(lines.clone(), 8);
+    assert_eq!(y.line_column(), (1, 4));
+    assert_eq!(y.original_line_column(), (1, 2));
+    assert_eq!(y.original_location().as_str(), "y");
+    let ffi = Location::new(lines.clone(), 6);
+    assert_eq!(ffi.original_position(), 3);
+    assert_eq!(ffi.original_line_column(), (1, 1));
+    assert_eq!(Location::new(nfd.clone(), 4).original_location().position(), 4);
+
+    let chars = SourceBuilder::new()
+        .segmenter(Segmentation::Chars)
+        .finish_normalized("chars.txt", "e\u{301}x", NormalizationForm::Nfc);
+    assert_eq!(chars.contents(), "\u{e9}x");
+    assert_eq!(chars.len(), 2);
+    assert_eq!(chars.original_source().len(), 3);
+    assert_eq!(Location::new(chars.clone(), 1).original_position(), 2);
+}
+
+#[test]
+fn segmentation() {
+    let contents = "k\u{93f}e\u{301}\r\nx";
+    let source = Source::new("extended.txt", contents);
+    assert_eq!(source.segmentation(), Some(Segmentation::Extended));
+    assert_eq!(source.segmenter().count("k\u{93f}"), 1);
+    assert_eq!(source.len(), 4);
+
+    let legacy = SourceBuilder::new()
+        .segmenter(Segmentation::Legacy)
+        .finish("legacy.txt", contents);
+    assert_eq!(legacy.segmenter().count("k\u{93f}"), 2);
+    assert_eq!(&legacy[0], "k");
+    assert_eq!(&legacy[1], "\u{93f}");
+    assert_eq!(legacy.len(), 5);
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Segmentation::Chars);
+    assert_eq!(builder.segmentation(), Some(Segmentation::Chars));
+    assert_ne!(builder, SourceBuilder::new());
+    assert_eq!(&builder, SourceBuilder::new().segmenter(Segmentation::Chars));
+    let chars = builder.finish("chars.txt", contents);
+    assert_eq!(chars.len(), 7);
+    assert_eq!(chars.line_count(), 2);
+    assert!(!chars.full_span().segments().nth(4).unwrap().is_line_break());
+    assert_eq!(chars.full_span().end().line_column(), (1, 1));
+
+    let lazy = builder.chunk_size(5).finish_lazy("lazy.txt", contents);
+    assert_eq!(lazy.len(), 7);
+    assert_eq!(lazy.line_count(), 2);
+
+    let source = builder.finish("edit.txt", "a\rb");
+    let (edited, _) =
+        source.apply_edits(&[TextEdit::new(2 .. 3, "\nb")]).unwrap();
+    assert_eq!(edited.contents(), "a\r\nb");
+    assert_eq!(edited.line_count(), 2);
+    assert_eq!(Segmentation::Chars.count("e\u{301}"), 2);
+}
+
+#[test]
+fn word_and_sentence_segmenters() {
+    let contents = "Hello, world!\nBye now.";
+    let words = SourceBuilder::new()
+        .segmenter(Segmentation::Words)
+        .finish("w", contents);
+    let segments: Vec<_> = words
+        .full_span()
+        .segments()
+        .map(|segment| segment.to_string())
+        .collect();
+    assert_eq!(segments, [
+        "Hello", ",", " ", "world", "!", "\n", "Bye", " ", "now", "."
+    ]);
+    assert_eq!(words.line_count(), 2);
+    let bye = words.full_span().segments().nth(6).unwrap();
+    assert_eq!(bye.location().line_column(), (1, 0));
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Segmentation::Sentences);
+    let sentences = builder.finish("s", "One. Two?\nThree");
+    assert_eq!(&sentences[0], "One. ");
+    assert_eq!(&sentences[1], "Two?\n");
+    assert_eq!(&sentences[2], "Three");
+    assert_eq!(sentences.line_count(), 2);
+    assert_eq!(sentences.full_span().end().line_column(), (1, 1));
+
+    let lazy = builder.chunk_size(4).finish_lazy("lazy", "One. Two?\nThree");
+    assert_eq!(lazy.len(), 3);
+    assert_eq!(lazy.line_count(), 2);
+}
+
+#[test]
+fn lazy_word_and_sentence_segmenters() {
+    let contents = "Hello, wörld! Ça va?\r\nYes.\u{2028}No…  \n\nEnd";
+    for segmentation in [Segmentation::Words, Segmentation::Sentences] {
+        let mut builder = SourceBuilder::new();
+        builder.segmenter(segmentation);
+        let eager = builder.finish("eager.txt", contents);
+        for chunk_size in [1, 2, 3, 5, 8, 13, contents.len() + 1] {
+            let lazy = builder
+                .chunk_size(chunk_size)
+                .finish_lazy("lazy.txt", contents);
+            assert_eq!(
+                lazy.seg_byte_indices().collect::<Vec<_>>(),
+                eager.seg_byte_indices().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                lazy.newline_indices().collect::<Vec<_>>(),
+                eager.newline_indices().collect::<Vec<_>>()
+            );
+            for position in 0 ..= eager.len() {
+                assert_eq!(
+                    Location::new(lazy.clone(), position).line_column(),
+                    Location::new(eager.clone(), position).line_column()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn custom_segmenter() {
+    #[derive(Debug)]
+    struct Lines;
+
+    impl Segmenter for Lines {
+        fn segment_indices<'text>(
+            &self,
+            text: &'text str,
+        ) -> SegmentIndices<'text> {
+            let lines = text.split_inclusive('\n').scan(0, |start, line| {
+                let index = *start;
+                *start += line.len();
+                Some((index, line))
+            });
+            SegmentIndices::new(text, lines)
+        }
+    }
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Lines);
+    assert_eq!(builder.segmentation(), None);
+    assert_eq!(builder, builder.clone());
+    assert_ne!(&builder, SourceBuilder::new().segmenter(Lines));
+    let source = builder.finish("lines", "ab\ncd\n\nef");
+    assert_eq!(source.segmentation(), None);
+    assert_eq!(source.segmenter().segment_indices("a\nb").clone().count(), 2);
+    assert_eq!(source.len(), 4);
+    assert_eq!(&source[1], "cd\n");
+    assert_eq!(source.line_count(), 4);
+    let (edited, _) =
+        source.apply_edits(&[TextEdit::new(1 .. 2, "x\ny")]).unwrap();
+    assert_eq!(edited.contents(), "ab\nx\ny\nef");
+    assert_eq!(edited.len(), 4);
+    assert_eq!(&edited[2], "y\n");
+}
@@ -5,29 +5,26 @@ mod test;
 
 use crate::{
     location::{LocatedSegment, Location},
-    source::Source,
+    source::{FileId, FileSpan, Source},
 };
 #[cfg(feature = "parse")]
 use nom::{
     error::ParseError,
-    Compare,
-    FindToken,
     InputIter,
     InputLength,
     InputTake,
     InputTakeAtPosition,
-    Offset,
     Slice,
 };
-#[cfg(feature = "parse")]
-use std::iter::Enumerate;
 use std::{
     borrow::Borrow,
     cmp::Ordering,
     fmt,
     hash::{Hash, Hasher},
-    ops::{Bound, Deref, RangeBounds},
+    ops::{Bound, Deref, Range, RangeBounds},
 };
+#[cfg(any(feature = "parse", feature = "nom8"))]
+use std::{iter::Enumerate, str::FromStr};
 
 /// A span (a range) in the source code.
 ///
@@ -53,7 +50,8 @@ impl Span {
     /// Panics if `length` is too big, i.e. `start.position() + length >
     /// start.source().len()`.
     pub fn new(start: Location, length: usize) -> Self {
-        if start.source().len() - start.position() < length {
+        let end = start.position().checked_add(length);
+        if end.and_then(|end| start.source().try_byte_index(end)).is_none() {
             panic!(
                 "Span length is too big; available size: {}, given: {}",
                 start.source().len() - start.position(),
@@ -121,11 +119,49 @@ impl Span {
         self.length
     }
 
+    /// Tests whether this span contains no segments.
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    /// The range of bytes this span covers in the source code contents.
+    pub fn byte_range(&self) -> Range<usize> {
+        self.start.byte_offset() .. self.end().byte_offset()
+    }
+
+    /// The range of this span in the original contents of the source, before
+    /// normalization, in terms of bytes (see [`Source::new_normalized`]). A
+    /// span covering part of what an original grapheme cluster normalized to
+    /// covers the whole cluster. The same as [`Span::byte_range`] if the
+    /// source was not normalized.
+    pub fn original_byte_range(&self) -> Range<usize> {
+        let source = self.source();
+        let start = source.original_byte_index(self.start.byte_offset(), false);
+        let end = source.original_byte_index(self.end().byte_offset(), true);
+        start .. end
+    }
+
     /// The source code object this span refers to.
     pub fn source(&self) -> &Source {
         self.start.source()
     }
 
+    /// The ID of the source code object in its [`SourceMap`], if any.
+    ///
+    /// [`SourceMap`]: crate::source::SourceMap
+    pub fn file_id(&self) -> Option<FileId> {
+        self.source().file_id()
+    }
+
+    /// This span in the compact form of a [`SourceMap`], carrying the file ID
+    /// rather than the source. Returns `None` if the source is not registered
+    /// in a map.
+    ///
+    /// [`SourceMap`]: crate::source::SourceMap
+    pub fn file_span(&self) -> Option<FileSpan> {
+        Some(FileSpan::new(self.start.file_location()?, self.length))
+    }
+
     /// Gets the string this span includes as a whole.
     pub fn as_str(&self) -> &str {
         let start = self.start.position();
@@ -141,14 +177,9 @@ impl Span {
     /// Expands this span in order to contain the whole lines the original span
     /// contains.
     pub fn expand_lines(&self) -> Span {
-        let start_line = self.start().line();
-        let end_line = self.end().line();
-        let init = self.source().line_start(start_line);
-        let end = self
-            .source()
-            .try_line_start(end_line + 1)
-            .unwrap_or(self.source().len());
-        Self::new(Location::new(self.source().clone(), init), end - init)
+        let start = self.start().line_span().start();
+        let end = self.end().line_span().end();
+        Self::from_range(start, end)
     }
 
     /// Slices this span to the given range. Returns `None` if the range is
@@ -180,6 +211,87 @@ impl Span {
         }
     }
 
+    /// Creates the smallest span containing both this span and `other`, as
+    /// when joining the spans of child nodes. Returns `None` if the spans
+    /// point to different sources.
+    pub fn join(&self, other: &Self) -> Option<Self> {
+        if self.source() != other.source() {
+            return None;
+        }
+        let start = self.start.position().min(other.start.position());
+        let end = self.end_position().max(other.end_position());
+        let start_loc = Location::new_unchecked(self.source().clone(), start);
+        Some(Self::new_unchecked(start_loc, end - start))
+    }
+
+    /// Creates the span of segments both this span and `other` contain. If the
+    /// spans only touch, the intersection is empty, placed where they touch.
+    /// Returns `None` if the spans point to different sources or are apart.
+    pub fn intersect(&self, other: &Self) -> Option<Self> {
+        if self.source() != other.source() {
+            return None;
+        }
+        let start = self.start.position().max(other.start.position());
+        let end = self.end_position().min(other.end_position());
+        if start > end {
+            return None;
+        }
+        let start_loc = Location::new_unchecked(self.source().clone(), start);
+        Some(Self::new_unchecked(start_loc, end - start))
+    }
+
+    /// Creates the span between this span and `other`, i.e. from the end of
+    /// the first span to the start of the second, regardless of the order
+    /// they are given. The gap is empty if the spans touch. Returns `None` if
+    /// the spans point to different sources or overlap.
+    pub fn between(&self, other: &Self) -> Option<Self> {
+        if self.source() != other.source() {
+            return None;
+        }
+        let (first, second) = if self.start.position() <= other.start.position()
+        {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let start = first.end_position();
+        let end = second.start.position();
+        if start > end {
+            return None;
+        }
+        let start_loc = Location::new_unchecked(self.source().clone(), start);
+        Some(Self::new_unchecked(start_loc, end - start))
+    }
+
+    /// Tests whether the segment at the given location is in this span. An
+    /// empty span contains no location, and locations of other sources are
+    /// never contained.
+    pub fn contains(&self, location: &Location) -> bool {
+        self.source() == location.source()
+            && self.start.position() <= location.position()
+            && location.position() < self.end_position()
+    }
+
+    /// Tests whether every segment of `other` is in this span, with `other`
+    /// placed within this span's bounds. Spans of other sources are never
+    /// contained.
+    pub fn contains_span(&self, other: &Self) -> bool {
+        self.source() == other.source()
+            && self.start.position() <= other.start.position()
+            && other.end_position() <= self.end_position()
+    }
+
+    /// Tests whether this span and `other` share at least one segment. Empty
+    /// spans and spans of different sources never overlap.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersect(other).is_some_and(|span| !span.is_empty())
+    }
+
+    /// The position where this span ends (exclusive).
+    fn end_position(&self) -> usize {
+        self.start.position() + self.length
+    }
+
     /// Creates an iterator over located grapheme cluster segments, namely
     /// [`LocatedSegment`]s.
     pub fn segments(&self) -> Segments {
@@ -252,7 +364,7 @@ impl InputLength for Span {
 }
 
 #[cfg(feature = "parse")]
-impl<'this> InputLength for &'this Span {
+impl InputLength for &Span {
     fn input_len(&self) -> usize {
         self.len()
     }
@@ -377,7 +489,7 @@ impl InputTakeAtPosition for Span {
             },
             Some(pos) => Ok(self.take_split(pos)),
             None => {
-                if self.len() > 0 {
+                if !self.is_empty() {
                     Ok(self.take_split(self.len()))
                 } else {
                     Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
@@ -387,190 +499,387 @@ impl InputTakeAtPosition for Span {
     }
 }
 
-#[cfg(feature = "parse")]
-impl Offset for Span {
-    fn offset(&self, second: &Self) -> usize {
-        second.start().position() - self.start().position()
-    }
-}
+#[cfg(feature = "nom8")]
+impl nom8::Input for Span {
+    type Item = LocatedSegment;
+    type Iter = Segments;
+    type IterIndices = Enumerate<Segments>;
 
-#[cfg(feature = "parse")]
-impl<'input> Compare<&'input Span> for Span {
-    fn compare(&self, input: &'input Span) -> nom::CompareResult {
-        let mut this_segments = self.segments();
-        let mut input_segments = input.segments();
-
-        loop {
-            match (this_segments.next(), input_segments.next()) {
-                (Some(this_segment), Some(input_segment)) => {
-                    if this_segment.as_str() != input_segment.as_str() {
-                        break nom::CompareResult::Error;
-                    }
-                },
-                (None, Some(_)) => break nom::CompareResult::Incomplete,
-                (_, None) => break nom::CompareResult::Ok,
-            }
-        }
+    fn input_len(&self) -> usize {
+        self.len()
     }
 
-    fn compare_no_case(&self, input: &'input Span) -> nom::CompareResult {
-        let mut this_segments = self.segments();
-        let mut input_segments = input.segments();
-
-        loop {
-            match (this_segments.next(), input_segments.next()) {
-                (Some(this_segment), Some(input_segment)) => {
-                    if this_segment.as_str().to_lowercase()
-                        != input_segment.as_str().to_lowercase()
-                    {
-                        break nom::CompareResult::Error;
-                    }
-                },
-                (None, Some(_)) => break nom::CompareResult::Incomplete,
-                (_, None) => break nom::CompareResult::Ok,
-            }
+    fn take(&self, index: usize) -> Self {
+        match self.try_slice(.. index) {
+            Some(span) => span,
+            None => panic!("position {} invalid on span", index),
         }
     }
-}
 
-#[cfg(feature = "parse")]
-impl Compare<Span> for Span {
-    fn compare(&self, input: Span) -> nom::CompareResult {
-        self.compare(&input)
+    fn take_from(&self, index: usize) -> Self {
+        match self.try_slice(index ..) {
+            Some(span) => span,
+            None => panic!("position {} invalid on span", index),
+        }
     }
 
-    fn compare_no_case(&self, input: Span) -> nom::CompareResult {
-        self.compare_no_case(&input)
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        (nom8::Input::take_from(self, index), nom8::Input::take(self, index))
     }
-}
 
-#[cfg(feature = "parse")]
-impl<'input> Compare<&'input SpanContent> for Span {
-    fn compare(&self, input: &'input SpanContent) -> nom::CompareResult {
-        self.compare(input.span())
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.segments().position(predicate)
     }
 
-    fn compare_no_case(
-        &self,
-        input: &'input SpanContent,
-    ) -> nom::CompareResult {
-        self.compare_no_case(input.span())
+    fn iter_elements(&self) -> Self::Iter {
+        self.segments()
     }
-}
 
-#[cfg(feature = "parse")]
-impl Compare<SpanContent> for Span {
-    fn compare(&self, input: SpanContent) -> nom::CompareResult {
-        self.compare(input.span())
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.segments().enumerate()
     }
 
-    fn compare_no_case(&self, input: SpanContent) -> nom::CompareResult {
-        self.compare_no_case(input.span())
+    fn slice_index(&self, count: usize) -> Result<usize, nom8::Needed> {
+        if self.len() >= count {
+            Ok(count)
+        } else {
+            Err(nom8::Needed::new(count - self.len()))
+        }
     }
 }
 
-#[cfg(feature = "parse")]
-impl<'this, 'input> Compare<&'input Span> for &'this Span {
-    fn compare(&self, input: &'input Span) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+/// Implements the traits nom 7 and nom 8 share (offset, comparison, token and
+/// substring search, parsing and extension) for [`Span`], given the nom crate.
+#[cfg(any(feature = "parse", feature = "nom8"))]
+macro_rules! impl_span_comparison {
+    ($nom:ident) => {
+        const _: () = {
+            use $nom::{
+                Compare,
+                ExtendInto,
+                FindSubstring,
+                FindToken,
+                Offset,
+                ParseTo,
+            };
+
+            impl Offset for Span {
+                fn offset(&self, second: &Self) -> usize {
+                    second.start().position() - self.start().position()
+                }
+            }
 
-    fn compare_no_case(&self, input: &'input Span) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+            impl<'input> Compare<&'input Span> for Span {
+                fn compare(&self, input: &'input Span) -> $nom::CompareResult {
+                    let mut this_segments = self.segments();
+                    let mut input_segments = input.segments();
+
+                    loop {
+                        match (this_segments.next(), input_segments.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if this_segment.as_str()
+                                    != input_segment.as_str()
+                                {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
 
-#[cfg(feature = "parse")]
-impl<'this> Compare<Span> for &'this Span {
-    fn compare(&self, input: Span) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+                fn compare_no_case(
+                    &self,
+                    input: &'input Span,
+                ) -> $nom::CompareResult {
+                    let folding = self.source().case_folding();
+                    let mut this_segments = self.segments();
+                    let mut input_segments = input.segments();
+
+                    loop {
+                        match (this_segments.next(), input_segments.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if !folding.eq_ignore_case(
+                                    &this_segment, &input_segment,
+                                ) {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+            }
 
-    fn compare_no_case(&self, input: Span) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+            impl Compare<Span> for Span {
+                fn compare(&self, input: Span) -> $nom::CompareResult {
+                    self.compare(&input)
+                }
 
-#[cfg(feature = "parse")]
-impl<'this, 'input> Compare<&'input SpanContent> for &'this Span {
-    fn compare(&self, input: &'input SpanContent) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+                fn compare_no_case(&self, input: Span) -> $nom::CompareResult {
+                    self.compare_no_case(&input)
+                }
+            }
 
-    fn compare_no_case(
-        &self,
-        input: &'input SpanContent,
-    ) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+            impl<'input> Compare<&'input SpanContent> for Span {
+                fn compare(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare(input.span())
+                }
 
-#[cfg(feature = "parse")]
-impl<'this> Compare<SpanContent> for &'this Span {
-    fn compare(&self, input: SpanContent) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+                fn compare_no_case(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(input.span())
+                }
+            }
 
-    fn compare_no_case(&self, input: SpanContent) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+            impl Compare<SpanContent> for Span {
+                fn compare(&self, input: SpanContent) -> $nom::CompareResult {
+                    self.compare(input.span())
+                }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok> FindToken<&'tok str> for &'this Span {
-    fn find_token(&self, token: &'tok str) -> bool {
-        self.segments().any(|segment| segment.as_str() == token)
-    }
-}
+                fn compare_no_case(
+                    &self,
+                    input: SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(input.span())
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'tok> FindToken<&'tok str> for Span {
-    fn find_token(&self, token: &'tok str) -> bool {
-        (&self).find_token(token)
-    }
-}
+            impl<'this, 'input> Compare<&'input Span> for &'this Span {
+                fn compare(&self, input: &'input Span) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok, 'tok_ref> FindToken<&'tok_ref &'tok str> for &'this Span {
-    fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
-        self.find_token(*token)
-    }
-}
+                fn compare_no_case(
+                    &self,
+                    input: &'input Span,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'tok, 'tok_ref> FindToken<&'tok_ref &'tok str> for Span {
-    fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
-        (&self).find_token(token)
-    }
-}
+            impl<'this> Compare<Span> for &'this Span {
+                fn compare(&self, input: Span) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok> FindToken<&'tok LocatedSegment> for &'this Span {
-    fn find_token(&self, token: &'tok LocatedSegment) -> bool {
-        self.segments().any(|segment| &segment == token)
-    }
-}
+                fn compare_no_case(&self, input: Span) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'tok> FindToken<&'tok LocatedSegment> for Span {
-    fn find_token(&self, token: &'tok LocatedSegment) -> bool {
-        (&self).find_token(token)
-    }
-}
+            impl<'this, 'input> Compare<&'input SpanContent> for &'this Span {
+                fn compare(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok> FindToken<LocatedSegment> for &'this Span {
-    fn find_token(&self, token: LocatedSegment) -> bool {
-        self.find_token(&token)
+                fn compare_no_case(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
+
+            impl<'this> Compare<SpanContent> for &'this Span {
+                fn compare(&self, input: SpanContent) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: SpanContent,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
+
+            impl<'this, 'tok> FindToken<&'tok str> for &'this Span {
+                fn find_token(&self, token: &'tok str) -> bool {
+                    self.segments().any(|segment| segment.as_str() == token)
+                }
+            }
+
+            impl<'tok> FindToken<&'tok str> for Span {
+                fn find_token(&self, token: &'tok str) -> bool {
+                    (&self).find_token(token)
+                }
+            }
+
+            impl<'this, 'tok, 'tok_ref> FindToken<&'tok_ref &'tok str>
+                for &'this Span
+            {
+                fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
+                    self.find_token(*token)
+                }
+            }
+
+            impl<'tok, 'tok_ref> FindToken<&'tok_ref &'tok str> for Span {
+                fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
+                    (&self).find_token(token)
+                }
+            }
+
+            impl<'this, 'tok> FindToken<&'tok LocatedSegment> for &'this Span {
+                fn find_token(&self, token: &'tok LocatedSegment) -> bool {
+                    self.segments().any(|segment| &segment == token)
+                }
+            }
+
+            impl<'tok> FindToken<&'tok LocatedSegment> for Span {
+                fn find_token(&self, token: &'tok LocatedSegment) -> bool {
+                    (&self).find_token(token)
+                }
+            }
+
+            impl<'this, 'tok> FindToken<LocatedSegment> for &'this Span {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    self.find_token(&token)
+                }
+            }
+
+            impl<'tok> FindToken<LocatedSegment> for Span {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    (&self).find_token(&token)
+                }
+            }
+
+            impl<'this> FindToken<LocatedSegment> for &'this str {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    token
+                        .location()
+                        .source()
+                        .segmenter()
+                        .segment_indices(self)
+                        .any(|(_, segment)| segment == token.as_str())
+                }
+            }
+
+            impl<'substr> FindSubstring<&'substr str> for Span {
+                fn find_substring(
+                    &self,
+                    substr: &'substr str,
+                ) -> Option<usize> {
+                    find_segments(
+                        self.segments(),
+                        self.source().segmenter().segment_indices(substr),
+                        |segment, (_, substr_segment)| {
+                            segment.as_str() == *substr_segment
+                        },
+                    )
+                }
+            }
+
+            impl<'substr> FindSubstring<&'substr Span> for Span {
+                fn find_substring(
+                    &self,
+                    substr: &'substr Span,
+                ) -> Option<usize> {
+                    find_segments(
+                        self.segments(),
+                        substr.segments(),
+                        |segment, substr_segment| {
+                            segment.as_str() == substr_segment.as_str()
+                        },
+                    )
+                }
+            }
+
+            impl FindSubstring<Span> for Span {
+                fn find_substring(&self, substr: Span) -> Option<usize> {
+                    self.find_substring(&substr)
+                }
+            }
+
+            impl<'substr> FindSubstring<&'substr SpanContent> for Span {
+                fn find_substring(
+                    &self,
+                    substr: &'substr SpanContent,
+                ) -> Option<usize> {
+                    self.find_substring(substr.span())
+                }
+            }
+
+            impl FindSubstring<SpanContent> for Span {
+                fn find_substring(&self, substr: SpanContent) -> Option<usize> {
+                    self.find_substring(substr.span())
+                }
+            }
+
+            impl<R> ParseTo<R> for Span
+            where
+                R: FromStr,
+            {
+                fn parse_to(&self) -> Option<R> {
+                    self.as_str().parse().ok()
+                }
+            }
+
+            impl ExtendInto for Span {
+                type Item = char;
+                type Extender = String;
+
+                fn new_builder(&self) -> Self::Extender {
+                    String::new()
+                }
+
+                fn extend_into(&self, acc: &mut Self::Extender) {
+                    acc.push_str(self.as_str());
+                }
+            }
+        };
+    };
+}
+
+/// Finds the position, in segments, of the first occurrence of `needle` in
+/// `haystack`, comparing segments with `eq`. Segments are compared whole, so a
+/// match always starts and ends at segment boundaries.
+#[cfg(any(feature = "parse", feature = "nom8"))]
+pub(crate) fn find_segments<H, N, F>(
+    mut haystack: H,
+    needle: N,
+    mut eq: F,
+) -> Option<usize>
+where
+    H: Iterator + Clone,
+    N: Iterator + Clone,
+    F: FnMut(&H::Item, &N::Item) -> bool,
+{
+    let mut position = 0;
+    loop {
+        let mut window = haystack.clone();
+        let found = needle.clone().all(|needle_segment| {
+            window.next().is_some_and(|segment| eq(&segment, &needle_segment))
+        });
+        if found {
+            break Some(position);
+        }
+        haystack.next()?;
+        position += 1;
     }
 }
 
 #[cfg(feature = "parse")]
-impl<'tok> FindToken<LocatedSegment> for Span {
-    fn find_token(&self, token: LocatedSegment) -> bool {
-        (&self).find_token(&token)
-    }
-}
+impl_span_comparison!(nom);
+#[cfg(feature = "nom8")]
+impl_span_comparison!(nom8);
 
 /// Iterator over located segments of a [`Span`]. Created by [`Span::segments`]
 /// or [`SpanContent::segments`], as well via [`IntoIterator`] trait.
@@ -595,7 +904,7 @@ impl Iterator for Segments {
     type Item = LocatedSegment;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.span.len() > 0 {
+        if !self.span.is_empty() {
             let segment = self.span.start.segment();
             self.span.length -= 1;
             *self.span.start.position_mut() += 1;
@@ -612,7 +921,7 @@ impl Iterator for Segments {
 
 impl DoubleEndedIterator for Segments {
     fn next_back(&mut self) -> Option<Self::Item> {
-        if self.span.len() > 0 {
+        if !self.span.is_empty() {
             self.span.length -= 1;
             let segment = self.span.end().segment();
             Some(segment)
@@ -792,7 +1101,7 @@ impl InputLength for SpanContent {
 }
 
 #[cfg(feature = "parse")]
-impl<'this> InputLength for &'this SpanContent {
+impl InputLength for &SpanContent {
     fn input_len(&self) -> usize {
         self.span.len()
     }
@@ -910,7 +1219,7 @@ impl InputTakeAtPosition for SpanContent {
             },
             Some(pos) => Ok(self.take_split(pos)),
             None => {
-                if self.len() > 0 {
+                if !self.is_empty() {
                     Ok(self.take_split(self.len()))
                 } else {
                     Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
@@ -920,164 +1229,298 @@ impl InputTakeAtPosition for SpanContent {
     }
 }
 
-#[cfg(feature = "parse")]
-impl Offset for SpanContent {
-    fn offset(&self, second: &Self) -> usize {
-        self.span.offset(&second.span)
-    }
-}
+#[cfg(feature = "nom8")]
+impl nom8::Input for SpanContent {
+    type Item = LocatedSegment;
+    type Iter = Segments;
+    type IterIndices = Enumerate<Segments>;
 
-#[cfg(feature = "parse")]
-impl<'input> Compare<&'input Span> for SpanContent {
-    fn compare(&self, input: &'input Span) -> nom::CompareResult {
-        self.span.compare(input)
+    fn input_len(&self) -> usize {
+        self.span.len()
     }
 
-    fn compare_no_case(&self, input: &'input Span) -> nom::CompareResult {
-        self.span.compare_no_case(input)
+    fn take(&self, index: usize) -> Self {
+        nom8::Input::take(&self.span, index).content()
     }
-}
 
-#[cfg(feature = "parse")]
-impl Compare<Span> for SpanContent {
-    fn compare(&self, input: Span) -> nom::CompareResult {
-        self.compare(&input)
+    fn take_from(&self, index: usize) -> Self {
+        nom8::Input::take_from(&self.span, index).content()
     }
 
-    fn compare_no_case(&self, input: Span) -> nom::CompareResult {
-        self.compare_no_case(&input)
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        (nom8::Input::take_from(self, index), nom8::Input::take(self, index))
     }
-}
 
-#[cfg(feature = "parse")]
-impl<'input> Compare<&'input SpanContent> for SpanContent {
-    fn compare(&self, input: &'input SpanContent) -> nom::CompareResult {
-        self.compare(input.span())
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.segments().position(predicate)
     }
 
-    fn compare_no_case(
-        &self,
-        input: &'input SpanContent,
-    ) -> nom::CompareResult {
-        self.compare_no_case(input.span())
+    fn iter_elements(&self) -> Self::Iter {
+        self.segments()
     }
-}
 
-#[cfg(feature = "parse")]
-impl Compare<SpanContent> for SpanContent {
-    fn compare(&self, input: SpanContent) -> nom::CompareResult {
-        self.compare(input.span())
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.segments().enumerate()
     }
 
-    fn compare_no_case(&self, input: SpanContent) -> nom::CompareResult {
-        self.compare_no_case(input.span())
+    fn slice_index(&self, count: usize) -> Result<usize, nom8::Needed> {
+        nom8::Input::slice_index(&self.span, count)
     }
 }
 
-#[cfg(feature = "parse")]
-impl<'this, 'input> Compare<&'input Span> for &'this SpanContent {
-    fn compare(&self, input: &'input Span) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+/// Implements the traits nom 7 and nom 8 share (offset, comparison, token and
+/// substring search, parsing and extension) for [`SpanContent`], given the nom
+/// crate.
+#[cfg(any(feature = "parse", feature = "nom8"))]
+macro_rules! impl_span_content_comparison {
+    ($nom:ident) => {
+        const _: () = {
+            use $nom::{
+                Compare,
+                ExtendInto,
+                FindSubstring,
+                FindToken,
+                Offset,
+                ParseTo,
+            };
 
-    fn compare_no_case(&self, input: &'input Span) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+            impl Offset for SpanContent {
+                fn offset(&self, second: &Self) -> usize {
+                    self.span.offset(&second.span)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this> Compare<Span> for &'this SpanContent {
-    fn compare(&self, input: Span) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+            impl<'input> Compare<&'input Span> for SpanContent {
+                fn compare(&self, input: &'input Span) -> $nom::CompareResult {
+                    self.span.compare(input)
+                }
 
-    fn compare_no_case(&self, input: Span) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+                fn compare_no_case(
+                    &self,
+                    input: &'input Span,
+                ) -> $nom::CompareResult {
+                    self.span.compare_no_case(input)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this, 'input> Compare<&'input SpanContent> for &'this SpanContent {
-    fn compare(&self, input: &'input SpanContent) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+            impl Compare<Span> for SpanContent {
+                fn compare(&self, input: Span) -> $nom::CompareResult {
+                    self.compare(&input)
+                }
 
-    fn compare_no_case(
-        &self,
-        input: &'input SpanContent,
-    ) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+                fn compare_no_case(&self, input: Span) -> $nom::CompareResult {
+                    self.compare_no_case(&input)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this> Compare<SpanContent> for &'this SpanContent {
-    fn compare(&self, input: SpanContent) -> nom::CompareResult {
-        (**self).compare(input)
-    }
+            impl<'input> Compare<&'input SpanContent> for SpanContent {
+                fn compare(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare(input.span())
+                }
 
-    fn compare_no_case(&self, input: SpanContent) -> nom::CompareResult {
-        (**self).compare_no_case(input)
-    }
-}
+                fn compare_no_case(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(input.span())
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok> FindToken<&'tok str> for &'this SpanContent {
-    fn find_token(&self, token: &'tok str) -> bool {
-        self.segments().any(|segment| segment.as_str() == token)
-    }
-}
+            impl Compare<SpanContent> for SpanContent {
+                fn compare(&self, input: SpanContent) -> $nom::CompareResult {
+                    self.compare(input.span())
+                }
 
-#[cfg(feature = "parse")]
-impl<'tok> FindToken<&'tok str> for SpanContent {
-    fn find_token(&self, token: &'tok str) -> bool {
-        (&self).find_token(token)
-    }
-}
+                fn compare_no_case(
+                    &self,
+                    input: SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(input.span())
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok, 'tok_ref> FindToken<&'tok_ref &'tok str>
-    for &'this SpanContent
-{
-    fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
-        self.find_token(*token)
-    }
-}
+            impl<'this, 'input> Compare<&'input Span> for &'this SpanContent {
+                fn compare(&self, input: &'input Span) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
 
-#[cfg(feature = "parse")]
-impl<'tok, 'tok_ref> FindToken<&'tok_ref &'tok str> for SpanContent {
-    fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
-        (&self).find_token(token)
-    }
-}
+                fn compare_no_case(
+                    &self,
+                    input: &'input Span,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok> FindToken<&'tok LocatedSegment> for &'this SpanContent {
-    fn find_token(&self, token: &'tok LocatedSegment) -> bool {
-        self.segments().any(|segment| &segment == token)
-    }
-}
+            impl<'this> Compare<Span> for &'this SpanContent {
+                fn compare(&self, input: Span) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
 
-#[cfg(feature = "parse")]
-impl<'tok> FindToken<&'tok LocatedSegment> for SpanContent {
-    fn find_token(&self, token: &'tok LocatedSegment) -> bool {
-        (&self).find_token(token)
-    }
-}
+                fn compare_no_case(&self, input: Span) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
 
-#[cfg(feature = "parse")]
-impl<'this, 'tok> FindToken<LocatedSegment> for &'this SpanContent {
-    fn find_token(&self, token: LocatedSegment) -> bool {
-        self.find_token(&token)
-    }
+            impl<'this, 'input> Compare<&'input SpanContent>
+                for &'this SpanContent
+            {
+                fn compare(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
+
+            impl<'this> Compare<SpanContent> for &'this SpanContent {
+                fn compare(&self, input: SpanContent) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: SpanContent,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
+
+            impl<'this, 'tok> FindToken<&'tok str> for &'this SpanContent {
+                fn find_token(&self, token: &'tok str) -> bool {
+                    self.segments().any(|segment| segment.as_str() == token)
+                }
+            }
+
+            impl<'tok> FindToken<&'tok str> for SpanContent {
+                fn find_token(&self, token: &'tok str) -> bool {
+                    (&self).find_token(token)
+                }
+            }
+
+            impl<'this, 'tok, 'tok_ref> FindToken<&'tok_ref &'tok str>
+                for &'this SpanContent
+            {
+                fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
+                    self.find_token(*token)
+                }
+            }
+
+            impl<'tok, 'tok_ref> FindToken<&'tok_ref &'tok str>
+                for SpanContent
+            {
+                fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
+                    (&self).find_token(token)
+                }
+            }
+
+            impl<'this, 'tok> FindToken<&'tok LocatedSegment>
+                for &'this SpanContent
+            {
+                fn find_token(&self, token: &'tok LocatedSegment) -> bool {
+                    self.segments().any(|segment| &segment == token)
+                }
+            }
+
+            impl<'tok> FindToken<&'tok LocatedSegment> for SpanContent {
+                fn find_token(&self, token: &'tok LocatedSegment) -> bool {
+                    (&self).find_token(token)
+                }
+            }
+
+            impl<'this, 'tok> FindToken<LocatedSegment> for &'this SpanContent {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    self.find_token(&token)
+                }
+            }
+
+            impl<'tok> FindToken<LocatedSegment> for SpanContent {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    (&self).find_token(&token)
+                }
+            }
+
+            impl<'substr> FindSubstring<&'substr str> for SpanContent {
+                fn find_substring(
+                    &self,
+                    substr: &'substr str,
+                ) -> Option<usize> {
+                    self.span().find_substring(substr)
+                }
+            }
+
+            impl<'substr> FindSubstring<&'substr Span> for SpanContent {
+                fn find_substring(
+                    &self,
+                    substr: &'substr Span,
+                ) -> Option<usize> {
+                    self.span().find_substring(substr)
+                }
+            }
+
+            impl FindSubstring<Span> for SpanContent {
+                fn find_substring(&self, substr: Span) -> Option<usize> {
+                    self.span().find_substring(&substr)
+                }
+            }
+
+            impl<'substr> FindSubstring<&'substr SpanContent> for SpanContent {
+                fn find_substring(
+                    &self,
+                    substr: &'substr SpanContent,
+                ) -> Option<usize> {
+                    self.span().find_substring(substr.span())
+                }
+            }
+
+            impl FindSubstring<SpanContent> for SpanContent {
+                fn find_substring(&self, substr: SpanContent) -> Option<usize> {
+                    self.span().find_substring(substr.span())
+                }
+            }
+
+            impl<R> ParseTo<R> for SpanContent
+            where
+                R: FromStr,
+            {
+                fn parse_to(&self) -> Option<R> {
+                    self.as_str().parse().ok()
+                }
+            }
+
+            impl ExtendInto for SpanContent {
+                type Item = char;
+                type Extender = String;
+
+                fn new_builder(&self) -> Self::Extender {
+                    String::new()
+                }
+
+                fn extend_into(&self, acc: &mut Self::Extender) {
+                    acc.push_str(self.as_str());
+                }
+            }
+        };
+    };
 }
 
 #[cfg(feature = "parse")]
-impl<'tok> FindToken<LocatedSegment> for SpanContent {
-    fn find_token(&self, token: LocatedSegment) -> bool {
-        (&self).find_token(&token)
-    }
-}
+impl_span_content_comparison!(nom);
+#[cfg(feature = "nom8")]
+impl_span_content_comparison!(nom8);
 
 /// A type for metadata associated with a span ("spanned data").
 #[derive(Debug, Clone)]
@@ -1,4 +1,5 @@
-use crate::{source::Source, Span};
+use crate::{source::Source, ColumnUnit, Span};
+#[cfg(feature = "parse")]
 use nom::Slice;
 
 #[test]
@@ -67,6 +68,7 @@ fn invalid_range_inclusive_inclusive() {
     );
 }
 
+#[cfg(feature = "parse")]
 #[test]
 fn segments() {
     let source = Source::new("complicated.rs", "av́e\nmař̋ia\ns̋ic̄");
@@ -111,3 +113,356 @@ fn segments() {
 
     assert_eq!(iterator.next(), None);
 }
+
+#[test]
+fn column_units() {
+    let source = Source::new("units.txt", "x\nñ̃日😀\u{1F1E7}\u{1F1F7}z");
+    let segments: Vec<_> = source.full_span().segments().collect();
+    let location = segments[6].location();
+    assert_eq!(location.as_str(), "z");
+    assert_eq!(location.line_column(), (1, 4));
+    assert_eq!(location.line_column_in(ColumnUnit::Segments), (1, 4));
+    assert_eq!(location.column_in(ColumnUnit::Chars), 6);
+    assert_eq!(location.column_in(ColumnUnit::Utf16), 9);
+    assert_eq!(location.column_in(ColumnUnit::Bytes), 19);
+    assert_eq!(location.column_in(ColumnUnit::DisplayWidth), 7);
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn join_and_intersect() {
+    let source = Source::new("foo.rs", "abcdef");
+    let span = source.full_span();
+    let left = span.slice(1 .. 3);
+    let right = span.slice(2 .. 5);
+    assert_eq!(left.join(&right).unwrap().as_str(), "bcde");
+    assert_eq!(left.intersect(&right).unwrap().as_str(), "c");
+
+    let apart = span.slice(4 .. 6);
+    assert_eq!(left.join(&apart).unwrap().as_str(), "bcdef");
+    assert_eq!(left.intersect(&apart), None);
+    let touching = left.intersect(&span.slice(3 .. 4)).unwrap();
+    assert!(touching.is_empty());
+    assert_eq!(touching.start().position(), 3);
+
+    let other = Source::new("foo.rs", "abcdef").full_span();
+    assert_eq!(span.join(&other), None);
+    assert_eq!(span.intersect(&other), None);
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn between() {
+    let source = Source::new("foo.rs", "abcdef");
+    let span = source.full_span();
+    let left = span.slice(0 .. 2);
+    let right = span.slice(4 .. 5);
+    assert_eq!(left.between(&right).unwrap().as_str(), "cd");
+    assert_eq!(right.between(&left).unwrap().as_str(), "cd");
+    assert!(left.between(&span.slice(2 .. 3)).unwrap().is_empty());
+    assert_eq!(left.between(&span.slice(1 .. 3)), None);
+    let other = Source::new("foo.rs", "abcdef").full_span();
+    assert_eq!(left.between(&other), None);
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn containment_and_overlap() {
+    let source = Source::new("foo.rs", "abcdef");
+    let span = source.full_span();
+    let inner = span.slice(1 .. 4);
+    assert!(inner.contains(&span.slice(1 ..).start()));
+    assert!(!inner.contains(&inner.end()));
+    assert!(!span.slice(2 .. 2).contains(&span.slice(2 ..).start()));
+    assert!(span.contains_span(&inner));
+    assert!(inner.contains_span(&span.slice(4 .. 4)));
+    assert!(!inner.contains_span(&span));
+    assert!(inner.overlaps(&span.slice(3 .. 6)));
+    assert!(!inner.overlaps(&span.slice(4 .. 6)));
+    assert!(!inner.overlaps(&span.slice(2 .. 2)));
+
+    let other = Source::new("foo.rs", "abcdef").full_span();
+    assert!(!span.contains(&other.start()));
+    assert!(!span.contains_span(&other));
+    assert!(!span.overlaps(&other));
+}
+
+#[test]
+fn tag_table() {
+    struct Tags {
+        carriage_return: Span,
+        line_feed: Span,
+        acute: Span,
+        empty: Span,
+    }
+
+    let tags = crate::tag_table! {
+        Tags {
+            carriage_return: "\r",
+            line_feed: "\n",
+            acute: &String::from("e\u{301}"),
+            empty: "",
+        }
+    };
+    assert_eq!(tags.carriage_return.as_str(), "\r");
+    assert_eq!(tags.line_feed.as_str(), "\n");
+    assert_eq!(tags.acute.as_str(), "e\u{301}");
+    assert_eq!(tags.acute.len(), 1);
+    assert!(tags.empty.is_empty());
+    assert_eq!(tags.empty.source().name(), "Tags");
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn owned_tag() {
+    use crate::{
+        parse::OwnedTag,
+        source::{Segmentation, SourceBuilder},
+    };
+    use nom::{
+        bytes::complete::{tag, tag_no_case},
+        IResult,
+    };
+
+    let keyword: OwnedTag = "atn\u{329}\u{30a}".parse().unwrap();
+    assert_eq!(keyword.len(), 3);
+    assert_eq!(keyword, OwnedTag::from("atn\u{329}\u{30a}"));
+
+    let source = Source::new("foo.rs", "atn\u{329}\u{30a}ATN\u{329}\u{30a}atn");
+    let span = source.full_span();
+    let result: IResult<Span, Span> = tag(&keyword)(span);
+    let (rest, matched) = result.unwrap();
+    assert_eq!(matched.as_str(), "atn\u{329}\u{30a}");
+    let result: IResult<Span, Span> = tag(&keyword)(rest.clone());
+    assert!(result.is_err());
+    let result: IResult<Span, Span> = tag_no_case(&keyword)(rest);
+    let (rest, matched) = result.unwrap();
+    assert_eq!(matched.as_str(), "ATN\u{329}\u{30a}");
+    let result: IResult<Span, Span> = tag(keyword)(rest);
+    assert!(result.is_err());
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Segmentation::Words);
+    let source = builder.finish("foo.rs", "let x");
+    let result: IResult<Span, Span> =
+        tag(OwnedTag::new("let"))(source.full_span());
+    assert!(result.is_err());
+    let keyword = OwnedTag::new_with(&builder, "let");
+    assert_eq!(keyword.len(), 1);
+    let result: IResult<Span, Span> = tag(keyword)(source.full_span());
+    let (rest, matched) = result.unwrap();
+    assert_eq!(matched.as_str(), "let");
+    assert_eq!(rest.as_str(), " x");
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn tag_no_case_folds_segments() {
+    use crate::{
+        parse::Tag,
+        source::{CaseFolding, SourceBuilder},
+    };
+    use nom::{bytes::complete::tag_no_case, IResult};
+
+    let source = Source::new("foo.rs", "STRAẞE");
+    let result: IResult<Span, Span> =
+        tag_no_case(Tag(&["s", "t", "r", "a", "ß", "e"]))(source.full_span());
+    assert_eq!(result.unwrap().1.as_str(), "STRAẞE");
+
+    let source = Source::new("foo.rs", "SS");
+    let result: IResult<Span, Span> =
+        tag_no_case(Tag(&["ß"]))(source.full_span());
+    assert!(result.is_err());
+
+    let source = SourceBuilder::new()
+        .case_folding(CaseFolding::Turkic)
+        .finish("foo.rs", "IİI");
+    let result: IResult<Span, Span> =
+        tag_no_case(Tag(&["ı", "i"]))(source.full_span());
+    let (rest, matched) = result.unwrap();
+    assert_eq!(matched.as_str(), "Iİ");
+    let result: IResult<Span, Span> = tag_no_case(Tag(&["i"]))(rest);
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn find_substring_on_boundaries() {
+    use nom::{bytes::complete::take_until, FindSubstring, IResult, ParseTo};
+
+    let source = Source::new("foo.rs", "ae\u{301}b e*/12");
+    let span = source.full_span();
+    assert_eq!(span.find_substring("e"), Some(4));
+    assert_eq!(span.find_substring("e\u{301}"), Some(1));
+    assert_eq!(span.find_substring("\u{301}"), None);
+    assert_eq!(span.find_substring(""), Some(0));
+    assert_eq!(span.find_substring(span.slice(3 ..)), Some(3));
+
+    let result: IResult<Span, Span> = take_until("*/")(span);
+    let (rest, comment) = result.unwrap();
+    assert_eq!(comment.as_str(), "ae\u{301}b e");
+    assert_eq!(rest.as_str(), "*/12");
+    let number: Option<u32> = rest.slice(2 ..).parse_to();
+    assert_eq!(number, Some(12));
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn escaped_transform_into_string() {
+    use crate::parse::{escaped_transform, Tag};
+    use nom::{
+        branch::alt,
+        bytes::complete::is_not,
+        combinator::value,
+        IResult,
+    };
+
+    let mut parser = escaped_transform(
+        is_not("\\"),
+        '\\',
+        alt((value("\"", Tag(&["\""])), value("\n", Tag(&["n"])))),
+    );
+    let source = Source::new("foo.rs", "a\\\"e\u{301}\\nb");
+    let result: IResult<Span, String> = parser(source.full_span());
+    let (rest, output) = result.unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(output, "a\"e\u{301}\nb");
+
+    let source = Source::new("foo.rs", "ab\\\u{301}n");
+    let result: IResult<Span, String> = parser(source.full_span());
+    let (rest, output) = result.unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(output, "ab\\\u{301}n");
+
+    let source = Source::new("foo.rs", "ab\\");
+    let result: IResult<Span, String> = parser(source.full_span());
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "nom8")]
+#[test]
+fn nom8_escaped_transform() {
+    use crate::parse8::{escaped_transform, Tag};
+    use nom8::{
+        branch::alt,
+        bytes::complete::is_not,
+        combinator::value,
+        IResult,
+        Parser,
+    };
+
+    let source = Source::new("foo.rs", "a\\\"e\u{301}\\nb");
+    let mut parser = escaped_transform(
+        is_not("\\"),
+        '\\',
+        alt((value("\"", Tag(&["\""])), value("\n", Tag(&["n"])))),
+    );
+    let result: IResult<Span, String> = parser.parse(source.full_span());
+    let (rest, output) = result.unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(output, "a\"e\u{301}\nb");
+
+    let source = Source::new("foo.rs", "ab\\\u{301}n");
+    let result: IResult<Span, String> = parser.parse(source.full_span());
+    let (rest, output) = result.unwrap();
+    assert!(rest.is_empty());
+    assert_eq!(output, "ab\\\u{301}n");
+
+    let source = Source::new("foo.rs", "ab\\");
+    let result: IResult<Span, String> = parser.parse(source.full_span());
+    assert!(result.is_err());
+}
+
+#[cfg(feature = "nom8")]
+#[test]
+fn nom8_tag() {
+    use crate::parse8::{OwnedTag, Tag};
+    use nom8::{bytes::complete::tag, IResult, Input, Parser};
+
+    let source = Source::new("foo.rs", "ab\u{301}cd");
+    let span = source.full_span();
+    let result: IResult<Span, Span> = tag(Tag(&["a", "b\u{301}"]))(span);
+    let (rest, matched) = result.unwrap();
+    assert_eq!(matched.as_str(), "ab\u{301}");
+    assert_eq!(rest.as_str(), "cd");
+    assert_eq!(Input::take(&rest, 1).as_str(), "c");
+    assert_eq!(Input::take_from(&rest, 1).as_str(), "d");
+
+    let keyword = OwnedTag::new("c");
+    let result: IResult<Span, Span> = tag(keyword)(rest.clone());
+    assert_eq!(result.unwrap().0.as_str(), "d");
+
+    let (rest, matched) = Tag(&["c"]).parse(rest).unwrap();
+    assert_eq!(matched.as_str(), "c");
+    assert!(Tag(&["c"]).parse(rest).is_err());
+}
+
+#[cfg(feature = "nom8")]
+#[test]
+fn nom8_input() {
+    use nom8::{error::Error, Input, Needed};
+
+    let source = Source::new("foo.rs", "ae\u{301}b\ncd");
+    let span = source.full_span();
+    assert_eq!(span.input_len(), 6);
+    let (suffix, prefix) = span.take_split(2);
+    assert_eq!(prefix.as_str(), "ae\u{301}");
+    assert_eq!(suffix.as_str(), "b\ncd");
+    assert_eq!(suffix.start().position(), 2);
+    assert_eq!(span.position(|segment| segment == "\n"), Some(3));
+    assert_eq!(span.iter_elements().nth(1).unwrap(), "e\u{301}");
+    assert_eq!(span.iter_indices().nth(4).unwrap().0, 4);
+    assert_eq!(span.slice_index(6), Ok(6));
+    assert_eq!(span.slice_index(8), Err(Needed::new(2)));
+
+    let result = span.split_at_position_complete::<_, Error<Span>>(|segment| {
+        segment == "\n"
+    });
+    let (rest, line) = result.unwrap();
+    assert_eq!(line.as_str(), "ae\u{301}b");
+    assert_eq!(rest.as_str(), "\ncd");
+    let content = span.content();
+    assert_eq!(content.take_from(4).as_str(), "cd");
+}
+
+#[cfg(feature = "nom8")]
+#[test]
+fn nom8_compare_and_find() {
+    use crate::parse8::{OwnedTag, Tag};
+    use nom8::{
+        bytes::complete::take_until,
+        Compare,
+        CompareResult,
+        FindSubstring,
+        FindToken,
+        IResult,
+    };
+
+    let source = Source::new("foo.rs", "STRAẞE e\u{301}*/");
+    let span = source.full_span();
+    assert_eq!(span.compare(Tag(&["S", "T", "R", "A"])), CompareResult::Ok);
+    assert_eq!(
+        span.compare(OwnedTag::new("STRAẞE e\u{301}*/!")),
+        CompareResult::Incomplete
+    );
+    assert_eq!(span.compare(Tag(&["S", "T", "A"])), CompareResult::Error);
+    assert_eq!(
+        span.compare_no_case(Tag(&["s", "t", "r", "a", "ß"])),
+        CompareResult::Ok
+    );
+    assert_eq!(
+        span.content().compare(Tag(&["S", "T", "R"])),
+        CompareResult::Ok
+    );
+
+    assert!(span.find_token("ẞ"));
+    assert!(!span.find_token("\u{301}"));
+    assert!(Tag(&["a", "b"]).find_token("b"));
+
+    assert_eq!(span.find_substring("e\u{301}"), Some(7));
+    assert_eq!(span.find_substring("e"), None);
+    let result: IResult<Span, Span> = take_until("*/")(span);
+    let (rest, before) = result.unwrap();
+    assert_eq!(before.as_str(), "STRAẞE e\u{301}");
+    assert_eq!(rest.as_str(), "*/");
+}
@@ -0,0 +1,666 @@
+//! This module provides growable sources, whose contents arrive in parts, such
+//! as from a REPL or a network connection, and spans to parse them with nom's
+//! streaming parsers.
+
+#[cfg(test)]
+mod test;
+
+#[cfg(any(feature = "parse", feature = "nom8"))]
+use crate::location::LocatedSegment;
+use crate::{
+    location::Location,
+    source::{Source, SourceBuilder},
+    span::{Segments, Span, Spanned},
+};
+#[cfg(feature = "parse")]
+use nom::{
+    error::ParseError,
+    Compare,
+    FindToken,
+    InputIter,
+    InputLength,
+    InputTake,
+    InputTakeAtPosition,
+    Offset,
+    Slice,
+};
+#[cfg(any(feature = "parse", feature = "nom8"))]
+use std::iter::Enumerate;
+use std::{fmt, ops::RangeBounds, sync::Arc};
+
+/// Identity shared by the snapshots of a [`StreamingSource`], telling apart
+/// streams with the same name. Identities are equal only if cloned from the
+/// same one.
+#[derive(Debug, Clone)]
+pub(crate) struct StreamId(Arc<()>);
+
+impl StreamId {
+    /// Creates a new identity, distinct from every other.
+    fn new() -> Self {
+        Self(Arc::new(()))
+    }
+}
+
+impl PartialEq for StreamId {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for StreamId {}
+
+/// A source code object whose contents grow as more text is appended.
+///
+/// Each append creates a new snapshot [`Source`]. The last grapheme
+/// cluster/segment of an open stream is unstable: appended text can extend it
+/// (e.g. a combining mark, or a `"\n"` after a `"\r"`). Segmenting by words
+/// or sentences makes the last two segments unstable, e.g. `"a:"` becomes a
+/// single word if `"b"` is appended. Therefore, spans of an open stream
+/// exclude the unstable segments, and positions up to their start keep
+/// referring to the same segments across appends.
+#[derive(Debug, Clone)]
+pub struct StreamingSource {
+    /// Current snapshot of the contents.
+    source: Source,
+    /// Whether no more text will be appended.
+    closed: bool,
+}
+
+impl StreamingSource {
+    /// Creates an empty open stream with the given name.
+    pub fn new<S>(name: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self::with_builder(&SourceBuilder::new(), name)
+    }
+
+    /// Creates an empty open stream with the given name, indexed with the
+    /// options of the given builder.
+    pub fn with_builder<S>(builder: &SourceBuilder, name: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        let source = builder.finish(name, "").into_snapshot(StreamId::new());
+        Self { source, closed: false }
+    }
+
+    /// Appends text to the stream, creating a new snapshot. Only the unstable
+    /// segments are segmented again together with the text, and the snapshot
+    /// shares the indices of the other segments with the previous one.
+    ///
+    /// Snapshots also share a buffer holding the contents, where the text is
+    /// written after the previous contents. When the buffer is full, the
+    /// contents are copied into a new one twice as large, so appending costs
+    /// amortized time linear in the text and the unstable segments, rather
+    /// than in the whole contents, and feeding a stream in many small parts
+    /// stays linear overall.
+    ///
+    /// # Panics
+    /// Panics if the stream is closed.
+    pub fn append(&mut self, text: &str) {
+        if self.closed {
+            panic!("Cannot append to closed stream {}", self.source);
+        }
+        self.source = self.source.append(text);
+    }
+
+    /// Closes the stream, marking that no more text will be appended. The last
+    /// segment becomes stable and spans are no longer partial.
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
+    /// Tests whether the stream was closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// The current snapshot of the contents, including the unstable last
+    /// segment.
+    pub fn source(&self) -> &Source {
+        &self.source
+    }
+
+    /// Number of segments that will not change anymore, i.e. every segment if
+    /// the stream is closed, or every segment but the ones appended text can
+    /// still change otherwise, usually the last one (see
+    /// [`Segmenter::unstable_segments`]).
+    ///
+    /// [`Segmenter::unstable_segments`]:
+    /// crate::source::Segmenter::unstable_segments
+    pub fn stable_len(&self) -> usize {
+        if self.closed {
+            self.source.len()
+        } else {
+            let unstable = self.source.segmenter().unstable_segments();
+            self.source.len().saturating_sub(unstable)
+        }
+    }
+
+    /// Creates a span over the stable segments of the current snapshot.
+    pub fn span(&self) -> StreamingSpan {
+        let start = Location::new(self.source.clone(), 0);
+        self.span_from_start(start)
+    }
+
+    /// Creates a span from the given location up to the last stable segment of
+    /// the current snapshot, so parsing can resume where it stopped. The
+    /// location can be from any previous snapshot of this stream (see
+    /// [`StreamingSource::rebase`]).
+    pub fn span_from(&self, location: &Location) -> Option<StreamingSpan> {
+        self.rebase(location).map(|start| self.span_from_start(start))
+    }
+
+    /// Translates a location from a previous snapshot of this stream into the
+    /// current snapshot. Returns `None` if the location is not from this
+    /// stream, is past beyond the stable segments, or no longer starts a
+    /// segment, e.g. the end of a snapshot ending with `"\r"`.
+    pub fn rebase(&self, location: &Location) -> Option<Location> {
+        let position = location.position();
+        let same_stream = location.source().stream_id()
+            == self.source.stream_id()
+            && position <= self.stable_len()
+            && self.source.try_byte_index(position)
+                == Some(location.byte_offset());
+        same_stream.then(|| Location::new(self.source.clone(), position))
+    }
+
+    /// Creates a span from the given location of the current snapshot up to
+    /// the last stable segment.
+    fn span_from_start(&self, start: Location) -> StreamingSpan {
+        let length = self.stable_len() - start.position();
+        StreamingSpan { span: Span::new(start, length), partial: !self.closed }
+    }
+}
+
+/// A span of a [`StreamingSource`]. While the stream is open, the span is
+/// partial: more segments may follow its end, and nom's streaming parsers get
+/// [`nom::Err::Incomplete`] when they need to see past it. Once the stream is
+/// closed, it behaves like a complete [`Span`].
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamingSpan {
+    /// The inner span.
+    span: Span,
+    /// Whether more segments may follow the end of the span.
+    partial: bool,
+}
+
+impl StreamingSpan {
+    /// The inner span.
+    pub fn as_span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Converts this into the inner span.
+    pub fn into_span(self) -> Span {
+        self.span
+    }
+
+    /// Tests whether more segments may follow the end of this span.
+    pub fn is_partial(&self) -> bool {
+        self.partial
+    }
+
+    /// The length of this span in string segments.
+    pub fn len(&self) -> usize {
+        self.span.len()
+    }
+
+    /// Tests whether this span contains no segments.
+    pub fn is_empty(&self) -> bool {
+        self.span.is_empty()
+    }
+
+    /// The start location of this span.
+    pub fn start(&self) -> Location {
+        self.span.start()
+    }
+
+    /// The end location of this span.
+    pub fn end(&self) -> Location {
+        self.span.end()
+    }
+
+    /// Gets the string this span includes as a whole.
+    pub fn as_str(&self) -> &str {
+        self.span.as_str()
+    }
+
+    /// Creates an iterator over located grapheme cluster segments.
+    pub fn segments(&self) -> Segments {
+        self.span.segments()
+    }
+
+    /// Slices this span to the given range. Returns `None` if the range is
+    /// invalid. The slice is partial only if this span is partial and the
+    /// slice reaches its end.
+    pub fn try_slice<R>(&self, range: R) -> Option<Self>
+    where
+        R: RangeBounds<usize>,
+    {
+        let span = self.span.try_slice(range)?;
+        let partial = self.partial && span.end() == self.span.end();
+        Some(Self { span, partial })
+    }
+}
+
+impl fmt::Debug for StreamingSpan {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("StreamingSpan")
+            .field("span", &self.span)
+            .field("partial", &self.partial)
+            .finish()
+    }
+}
+
+impl fmt::Display for StreamingSpan {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmtr, "{}", self.span)
+    }
+}
+
+impl AsRef<Span> for StreamingSpan {
+    fn as_ref(&self) -> &Span {
+        &self.span
+    }
+}
+
+impl AsRef<str> for StreamingSpan {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Spanned for StreamingSpan {
+    fn span(&self) -> Span {
+        self.span.clone()
+    }
+}
+
+#[cfg(feature = "parse")]
+impl InputLength for StreamingSpan {
+    fn input_len(&self) -> usize {
+        self.span.len()
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<R> Slice<R> for StreamingSpan
+where
+    R: RangeBounds<usize> + fmt::Debug + Clone,
+{
+    fn slice(&self, range: R) -> Self {
+        match self.try_slice(range.clone()) {
+            Some(span) => span,
+            None => panic!("range {:?} invalid on span", range),
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl InputIter for StreamingSpan {
+    type Item = LocatedSegment;
+    type Iter = Enumerate<Self::IterElem>;
+    type IterElem = Segments;
+
+    fn iter_indices(&self) -> Self::Iter {
+        self.iter_elements().enumerate()
+    }
+
+    fn iter_elements(&self) -> Self::IterElem {
+        self.segments()
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.segments().position(predicate)
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
+        self.span.slice_index(count)
+    }
+}
+
+#[cfg(feature = "parse")]
+impl InputTake for StreamingSpan {
+    fn take(&self, count: usize) -> Self {
+        let (_, prefix) = self.split_at(count);
+        prefix
+    }
+
+    fn take_split(&self, count: usize) -> (Self, Self) {
+        self.split_at(count)
+    }
+}
+
+#[cfg(feature = "parse")]
+impl InputTakeAtPosition for StreamingSpan {
+    type Item = LocatedSegment;
+
+    fn split_at_position<P, E>(
+        &self,
+        predicate: P,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(pos) => Ok(self.take_split(pos)),
+            None if self.partial => {
+                Err(nom::Err::Incomplete(nom::Needed::new(1)))
+            },
+            None => Ok(self.take_split(self.input_len())),
+        }
+    }
+
+    fn split_at_position1<P, E>(
+        &self,
+        predicate: P,
+        e: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(0) => {
+                Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+            },
+            Some(pos) => Ok(self.take_split(pos)),
+            None if self.partial => {
+                Err(nom::Err::Incomplete(nom::Needed::new(1)))
+            },
+            None => self.split_at_position1_complete(|_| false, e),
+        }
+    }
+
+    fn split_at_position_complete<P, E>(
+        &self,
+        predicate: P,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(pos) => Ok(self.take_split(pos)),
+            None => Ok(self.take_split(self.input_len())),
+        }
+    }
+
+    fn split_at_position1_complete<P, E>(
+        &self,
+        predicate: P,
+        e: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(0) => {
+                Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+            },
+            Some(pos) => Ok(self.take_split(pos)),
+            None => {
+                if !self.is_empty() {
+                    Ok(self.take_split(self.input_len()))
+                } else {
+                    Err(nom::Err::Error(E::from_error_kind(self.clone(), e)))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl Offset for StreamingSpan {
+    fn offset(&self, second: &Self) -> usize {
+        self.span.offset(&second.span)
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<T> Compare<T> for StreamingSpan
+where
+    Span: Compare<T>,
+{
+    fn compare(&self, input: T) -> nom::CompareResult {
+        self.complete_result(self.span.compare(input))
+    }
+
+    fn compare_no_case(&self, input: T) -> nom::CompareResult {
+        self.complete_result(self.span.compare_no_case(input))
+    }
+}
+
+#[cfg(any(feature = "parse", feature = "nom8"))]
+impl StreamingSpan {
+    /// Splits at the given position, respecting nom's order:
+    /// `(suffix, prefix)`.
+    fn split_at(&self, count: usize) -> (Self, Self) {
+        let (suffix, prefix) =
+            (self.span.try_slice(count ..), self.span.try_slice(.. count));
+        match (suffix, prefix) {
+            (Some(suffix), Some(prefix)) => {
+                (Self { span: suffix, partial: self.partial }, Self {
+                    span: prefix,
+                    partial: false,
+                })
+            },
+            _ => panic!("position {} invalid on span", count),
+        }
+    }
+
+    /// Turns an incomplete comparison into a mismatch if no more segments can
+    /// follow this span.
+    #[cfg(feature = "parse")]
+    fn complete_result(
+        &self,
+        result: nom::CompareResult,
+    ) -> nom::CompareResult {
+        match result {
+            nom::CompareResult::Incomplete if !self.partial => {
+                nom::CompareResult::Error
+            },
+            result => result,
+        }
+    }
+
+    /// Turns an incomplete nom 8 comparison into a mismatch if no more
+    /// segments can follow this span.
+    #[cfg(feature = "nom8")]
+    fn complete_result8(
+        &self,
+        result: nom8::CompareResult,
+    ) -> nom8::CompareResult {
+        match result {
+            nom8::CompareResult::Incomplete if !self.partial => {
+                nom8::CompareResult::Error
+            },
+            result => result,
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<T> FindToken<T> for StreamingSpan
+where
+    Span: FindToken<T>,
+{
+    fn find_token(&self, token: T) -> bool {
+        self.span.find_token(token)
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl nom8::Input for StreamingSpan {
+    type Item = LocatedSegment;
+    type Iter = Segments;
+    type IterIndices = Enumerate<Segments>;
+
+    fn input_len(&self) -> usize {
+        self.span.len()
+    }
+
+    fn take(&self, index: usize) -> Self {
+        let (_, prefix) = self.split_at(index);
+        prefix
+    }
+
+    fn take_from(&self, index: usize) -> Self {
+        let (suffix, _) = self.split_at(index);
+        suffix
+    }
+
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        self.split_at(index)
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.segments().position(predicate)
+    }
+
+    fn iter_elements(&self) -> Self::Iter {
+        self.segments()
+    }
+
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.segments().enumerate()
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, nom8::Needed> {
+        nom8::Input::slice_index(&self.span, count)
+    }
+
+    fn split_at_position<P, E>(
+        &self,
+        predicate: P,
+    ) -> nom8::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom8::error::ParseError<Self>,
+    {
+        match self.segments().position(predicate) {
+            Some(pos) => Ok(self.split_at(pos)),
+            None if self.partial => {
+                Err(nom8::Err::Incomplete(nom8::Needed::new(1)))
+            },
+            None => Ok(self.split_at(self.len())),
+        }
+    }
+
+    fn split_at_position1<P, E>(
+        &self,
+        predicate: P,
+        e: nom8::error::ErrorKind,
+    ) -> nom8::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom8::error::ParseError<Self>,
+    {
+        match self.segments().position(predicate) {
+            Some(0) => {
+                Err(nom8::Err::Error(E::from_error_kind(self.clone(), e)))
+            },
+            Some(pos) => Ok(self.split_at(pos)),
+            None if self.partial => {
+                Err(nom8::Err::Incomplete(nom8::Needed::new(1)))
+            },
+            None if self.is_empty() => {
+                Err(nom8::Err::Error(E::from_error_kind(self.clone(), e)))
+            },
+            None => Ok(self.split_at(self.len())),
+        }
+    }
+
+    fn split_at_position_mode<OM, P, E>(
+        &self,
+        predicate: P,
+    ) -> nom8::PResult<OM, Self, Self, E>
+    where
+        OM: nom8::OutputMode,
+        P: Fn(Self::Item) -> bool,
+        E: nom8::error::ParseError<Self>,
+    {
+        let pos = match self.segments().position(predicate) {
+            Some(pos) => pos,
+            None if self.partial
+                && <OM::Incomplete as nom8::IsStreaming>::is_streaming() =>
+            {
+                return Err(nom8::Err::Incomplete(nom8::Needed::new(1)));
+            },
+            None => self.len(),
+        };
+        let (suffix, prefix) = self.split_at(pos);
+        Ok((suffix, <OM::Output as nom8::Mode>::bind(|| prefix)))
+    }
+
+    fn split_at_position_mode1<OM, P, E>(
+        &self,
+        predicate: P,
+        e: nom8::error::ErrorKind,
+    ) -> nom8::PResult<OM, Self, Self, E>
+    where
+        OM: nom8::OutputMode,
+        P: Fn(Self::Item) -> bool,
+        E: nom8::error::ParseError<Self>,
+    {
+        let pos = match self.segments().position(predicate) {
+            Some(pos) => pos,
+            None if self.partial
+                && <OM::Incomplete as nom8::IsStreaming>::is_streaming() =>
+            {
+                return Err(nom8::Err::Incomplete(nom8::Needed::new(1)));
+            },
+            None => self.len(),
+        };
+        if pos == 0 {
+            return Err(nom8::Err::Error(<OM::Error as nom8::Mode>::bind(
+                || E::from_error_kind(self.clone(), e),
+            )));
+        }
+        let (suffix, prefix) = self.split_at(pos);
+        Ok((suffix, <OM::Output as nom8::Mode>::bind(|| prefix)))
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl nom8::Offset for StreamingSpan {
+    fn offset(&self, second: &Self) -> usize {
+        nom8::Offset::offset(&self.span, &second.span)
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl<T> nom8::Compare<T> for StreamingSpan
+where
+    Span: nom8::Compare<T>,
+{
+    fn compare(&self, input: T) -> nom8::CompareResult {
+        let result = nom8::Compare::compare(&self.span, input);
+        self.complete_result8(result)
+    }
+
+    fn compare_no_case(&self, input: T) -> nom8::CompareResult {
+        let result = nom8::Compare::compare_no_case(&self.span, input);
+        self.complete_result8(result)
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl<T> nom8::FindToken<T> for StreamingSpan
+where
+    Span: nom8::FindToken<T>,
+{
+    fn find_token(&self, token: T) -> bool {
+        nom8::FindToken::find_token(&self.span, token)
+    }
+}
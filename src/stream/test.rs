@@ -0,0 +1,228 @@
+use super::StreamingSource;
+use crate::{
+    source::{Segmentation, SourceBuilder},
+    Source,
+};
+
+#[test]
+fn unstable_last_segment() {
+    let mut stream = StreamingSource::new("stdin");
+    stream.append("ab\r");
+    assert_eq!(stream.span().as_str(), "ab");
+    let location = stream.span().end();
+
+    stream.append("\ncd");
+    assert_eq!(stream.source().len(), 5);
+    assert_eq!(stream.span().as_str(), "ab\r\nc");
+    let rebased = stream.rebase(&location).unwrap();
+    assert_eq!(rebased.as_str(), "\r\n");
+    assert_eq!(stream.span_from(&location).unwrap().as_str(), "\r\nc");
+
+    stream.append("\u{301}");
+    assert_eq!(stream.source().len(), 5);
+    assert_eq!(stream.span().as_str(), "ab\r\nc");
+    stream.close();
+    assert_eq!(stream.span().as_str(), "ab\r\ncd\u{301}");
+    assert!(!stream.span().is_partial());
+}
+
+#[test]
+fn foreign_location() {
+    let mut stream = StreamingSource::new("stdin");
+    stream.append("abc");
+    let other = StreamingSource::new("other");
+    assert!(other.rebase(&stream.span().start()).is_none());
+    let mut namesake = StreamingSource::new("stdin");
+    namesake.append("abc");
+    assert!(namesake.rebase(&stream.span().start()).is_none());
+    assert!(stream
+        .rebase(&Source::new("stdin", "abc").full_span().start())
+        .is_none());
+    assert!(stream.rebase(&stream.source().full_span().end()).is_none());
+}
+
+#[test]
+fn many_appends() {
+    let parts =
+        ["ab\r", "\n", "e", "\u{301}\u{302}", "\r", "x\u{2028}", "🇧", "🇷"];
+    let mut stream = StreamingSource::new("stdin");
+    let mut contents = String::new();
+    for step in 0 .. 1000 {
+        let part = parts[step % parts.len()];
+        stream.append(part);
+        contents.push_str(part);
+        if step % 97 == 0 {
+            let fresh = Source::new("stdin", contents.as_str());
+            assert_eq!(
+                stream.source().seg_byte_indices().collect::<Vec<_>>(),
+                fresh.seg_byte_indices().collect::<Vec<_>>()
+            );
+            assert_eq!(
+                stream.source().newline_indices().collect::<Vec<_>>(),
+                fresh.newline_indices().collect::<Vec<_>>()
+            );
+        }
+    }
+    let first = stream.span().start();
+    stream.append("z");
+    assert_eq!(stream.rebase(&first).unwrap().as_str(), "a");
+}
+
+#[test]
+fn shared_contents() {
+    let mut stream = StreamingSource::new("stdin");
+    let mut snapshots = Vec::new();
+    let mut contents = String::new();
+    for step in 0 .. 300 {
+        let part = format!("{} ", step);
+        stream.append(&part);
+        contents.push_str(&part);
+        snapshots.push((stream.source().clone(), contents.clone()));
+    }
+    for (snapshot, contents) in &snapshots {
+        assert_eq!(snapshot.contents(), contents);
+    }
+
+    let (old, old_contents) = &snapshots[10];
+    let fork = old.append("x");
+    assert_eq!(fork.contents(), old_contents.clone() + "x");
+    assert_eq!(old.contents(), old_contents);
+    assert_eq!(snapshots[11].0.contents(), snapshots[11].1);
+    assert_eq!(fork.append("y").contents(), old_contents.clone() + "xy");
+}
+
+#[test]
+fn word_and_sentence_streams() {
+    let text = "a:b 1,5 x.y. \"Hi.\" (yes) a:: ok. Then? no.  and so on";
+    for segmentation in [Segmentation::Words, Segmentation::Sentences] {
+        let mut builder = SourceBuilder::new();
+        builder.segmenter(segmentation);
+        for chunk_size in [1, 2, 3, 5] {
+            let mut stream = StreamingSource::with_builder(&builder, "stdin");
+            let mut contents = String::new();
+            let chars: Vec<_> = text.chars().collect();
+            for chunk in chars.chunks(chunk_size) {
+                let stable: Vec<_> = stream
+                    .span()
+                    .segments()
+                    .map(|segment| segment.as_str().to_owned())
+                    .collect();
+                let part: String = chunk.iter().collect();
+                stream.append(&part);
+                contents.push_str(&part);
+
+                let fresh = builder.finish("stdin", contents.as_str());
+                let fresh_segments: Vec<_> =
+                    fresh.full_span().segments().collect();
+                let segments: Vec<_> =
+                    stream.source().full_span().segments().collect();
+                assert_eq!(
+                    segments.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+                    fresh_segments
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>(),
+                    "{:?} of {:?}",
+                    segmentation,
+                    contents,
+                );
+                assert_eq!(
+                    segments[.. stable.len()]
+                        .iter()
+                        .map(|s| s.as_str())
+                        .collect::<Vec<_>>(),
+                    stable,
+                    "{:?} of {:?}",
+                    segmentation,
+                    contents,
+                );
+            }
+        }
+    }
+
+    let mut builder = SourceBuilder::new();
+    builder.segmenter(Segmentation::Words);
+    let mut stream = StreamingSource::with_builder(&builder, "stdin");
+    stream.append("a:");
+    assert_eq!(stream.stable_len(), 0);
+    stream.append("b");
+    assert_eq!(stream.source().len(), 1);
+    assert_eq!(stream.source().full_span().as_str(), "a:b");
+}
+
+#[cfg(feature = "parse")]
+#[test]
+fn incomplete_parsing() {
+    use crate::{parse::Tag, stream::StreamingSpan};
+    use nom::{
+        bytes::streaming::{tag, take_while1},
+        sequence::terminated,
+        IResult,
+    };
+
+    fn word(input: StreamingSpan) -> IResult<StreamingSpan, StreamingSpan> {
+        terminated(
+            take_while1(|segment: crate::LocatedSegment| {
+                segment.is_alphabetic()
+            }),
+            tag(Tag(&[";"])),
+        )(input)
+    }
+
+    let mut stream = StreamingSource::new("stdin");
+    stream.append("he");
+    assert!(matches!(word(stream.span()), Err(nom::Err::Incomplete(_))));
+    stream.append("llo;wo");
+    let (rest, parsed) = word(stream.span()).unwrap();
+    assert_eq!(parsed.as_str(), "hello");
+    assert_eq!(rest.as_str(), "w");
+    let resume = rest.start();
+
+    stream.append("rld");
+    let input = stream.span_from(&resume).unwrap();
+    assert!(matches!(word(input), Err(nom::Err::Incomplete(_))));
+    stream.close();
+    let input = stream.span_from(&resume).unwrap();
+    let prefix = nom::InputTake::take(&input, 2);
+    assert_eq!(prefix.as_str(), "wo");
+    assert!(!prefix.is_partial());
+    assert!(matches!(word(input), Err(nom::Err::Error(_))));
+}
+
+#[cfg(feature = "nom8")]
+#[test]
+fn incomplete_parsing_nom8() {
+    use crate::{parse8::Tag, stream::StreamingSpan};
+    use nom8::{
+        bytes::streaming::{tag, take_while1},
+        sequence::terminated,
+        IResult,
+        Parser,
+    };
+
+    fn word(input: StreamingSpan) -> IResult<StreamingSpan, StreamingSpan> {
+        terminated(
+            take_while1(|segment: crate::LocatedSegment| {
+                segment.is_alphabetic()
+            }),
+            tag(Tag(&[";"])),
+        )
+        .parse(input)
+    }
+
+    let mut stream = StreamingSource::new("stdin");
+    stream.append("he");
+    assert!(matches!(word(stream.span()), Err(nom8::Err::Incomplete(_))));
+    stream.append("llo;wo");
+    let (rest, parsed) = word(stream.span()).unwrap();
+    assert_eq!(parsed.as_str(), "hello");
+    assert_eq!(rest.as_str(), "w");
+    let resume = rest.start();
+
+    stream.append("rld");
+    let input = stream.span_from(&resume).unwrap();
+    assert!(matches!(word(input), Err(nom8::Err::Incomplete(_))));
+    stream.close();
+    let input = stream.span_from(&resume).unwrap();
+    assert!(matches!(word(input), Err(nom8::Err::Error(_))));
+}
@@ -0,0 +1,1308 @@
+//! This module defines tag types for segments. It also implements nom traits
+//! in order to be used with [`Span`] and [`SpanContent`].
+
+use crate::{
+    source::{CaseFolding, Segmentation, Segmenter, SourceBuilder},
+    span::{find_segments, Segments, Span, SpanContent, Spanned},
+    LocatedSegment,
+    Source,
+};
+#[cfg(feature = "parse")]
+use nom::{
+    InputIter,
+    InputLength,
+    InputTake,
+    InputTakeAtPosition,
+    Parser,
+    Slice,
+};
+#[cfg(feature = "parse")]
+use std::ops::RangeBounds;
+use std::{
+    convert::Infallible,
+    fmt,
+    hash::{Hash, Hasher},
+    iter::Enumerate,
+    marker::PhantomData,
+    mem,
+    slice,
+    str::FromStr,
+};
+
+/// A type usable as tag for a parser without having to create a proper
+/// [`Span`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Tag<'slice, 'seg>(
+    /// A sequence of segment contents.
+    pub &'slice [&'seg str],
+);
+
+impl<'slice, 'seg> Tag<'slice, 'seg> {
+    /// Returns the length of the tag in segments/grapheme clusters.
+    pub fn len(self) -> usize {
+        self.0.len()
+    }
+
+    /// Tests whether the tag contains no segments.
+    pub fn is_empty(self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Converts this tag parsed into a function (also a parser).
+    #[cfg(feature = "parse")]
+    pub fn into_fn<T, E>(
+        self,
+    ) -> impl FnMut(T) -> nom::IResult<T, T, E> + 'slice + 'seg
+    where
+        'slice: 'seg,
+        T: InputTake + nom::Compare<Self>,
+        E: nom::error::ParseError<T>,
+    {
+        move |input| nom::bytes::complete::tag(self)(input)
+    }
+
+    /// Converts this tag into a parser whose errors record this tag as the
+    /// expected item (see [`ExpectError`](crate::parse::ExpectError)).
+    pub fn expect<E>(self) -> ExpectTag<'slice, 'seg, E> {
+        ExpectTag { tag: self, _error: PhantomData }
+    }
+
+    /// Returns an iterator over the contents of segments of this tag.
+    pub fn segments(self) -> SegmentContents<'slice, 'seg> {
+        self.into_iter()
+    }
+}
+
+impl<'slice, 'seg> IntoIterator for Tag<'slice, 'seg> {
+    type Item = &'seg str;
+    type IntoIter = SegmentContents<'slice, 'seg>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SegmentContents { inner: self.0.iter() }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg, T, E> Parser<T, T, E> for Tag<'slice, 'seg>
+where
+    T: nom::InputTake + nom::Compare<Self>,
+    E: nom::error::ParseError<T>,
+{
+    fn parse(&mut self, input: T) -> nom::IResult<T, T, E> {
+        nom::bytes::complete::tag(*self)(input)
+    }
+}
+
+/// nom 8 parsers fix their error type, so a bare tag parses with nom's default
+/// error. Use `nom8::bytes::complete::tag` or [`Tag::expect`] for other error
+/// types.
+#[cfg(feature = "nom8")]
+impl<'slice, 'seg, T> nom8::Parser<T> for Tag<'slice, 'seg>
+where
+    T: nom8::Input + nom8::Compare<Self>,
+{
+    type Output = T;
+    type Error = nom8::error::Error<T>;
+
+    fn process<OM>(
+        &mut self,
+        input: T,
+    ) -> nom8::PResult<OM, T, Self::Output, Self::Error>
+    where
+        OM: nom8::OutputMode,
+    {
+        nom8::bytes::complete::tag(*self).process::<OM>(input)
+    }
+}
+
+/// Parser of a [`Tag`] whose errors record the tag as the expected item,
+/// created by [`Tag::expect`].
+pub struct ExpectTag<'slice, 'seg, E> {
+    /// The tag to be recognized.
+    tag: Tag<'slice, 'seg>,
+    /// Marks the error type, chosen by the caller.
+    _error: PhantomData<fn() -> E>,
+}
+
+impl<'slice, 'seg, E> ExpectTag<'slice, 'seg, E> {
+    /// The tag this parser recognizes.
+    pub fn tag(&self) -> Tag<'slice, 'seg> {
+        self.tag
+    }
+}
+
+impl<'slice, 'seg, E> fmt::Debug for ExpectTag<'slice, 'seg, E> {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.debug_struct("ExpectTag").field("tag", &self.tag).finish()
+    }
+}
+
+impl<'slice, 'seg, E> Clone for ExpectTag<'slice, 'seg, E> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'slice, 'seg, E> Copy for ExpectTag<'slice, 'seg, E> {}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg, T, E> Parser<T, T, E> for ExpectTag<'slice, 'seg, E>
+where
+    T: nom::InputTake + nom::Compare<Tag<'slice, 'seg>> + Clone,
+    E: crate::parse::ExpectError<T>,
+{
+    fn parse(&mut self, input: T) -> nom::IResult<T, T, E> {
+        match nom::bytes::complete::tag(self.tag)(input.clone()) {
+            Err(nom::Err::Error(_)) => Err(nom::Err::Error(E::from_expected(
+                input,
+                crate::parse::Expected::from(self.tag),
+            ))),
+            result => result,
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl<'slice, 'seg, T, E> nom8::Parser<T> for ExpectTag<'slice, 'seg, E>
+where
+    T: nom8::Input + nom8::Compare<Tag<'slice, 'seg>> + Clone,
+    E: crate::parse8::ExpectError<T>,
+{
+    type Output = T;
+    type Error = E;
+
+    fn process<OM>(&mut self, input: T) -> nom8::PResult<OM, T, T, E>
+    where
+        OM: nom8::OutputMode,
+    {
+        let mut parser = nom8::bytes::complete::tag::<_, _, E>(self.tag);
+        match parser.process::<OM>(input.clone()) {
+            Err(nom8::Err::Error(_)) => {
+                Err(nom8::Err::Error(<OM::Error as nom8::Mode>::bind(|| {
+                    E::from_expected(
+                        input,
+                        crate::parse8::Expected::from(self.tag),
+                    )
+                })))
+            },
+            result => result,
+        }
+    }
+}
+
+impl<'slice, 'seg, T> PartialEq<T> for Tag<'slice, 'seg>
+where
+    [&'seg str]: PartialEq<T>,
+{
+    fn eq(&self, other: &T) -> bool {
+        self.0 == other
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg> InputLength for Tag<'slice, 'seg> {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg, R> Slice<R> for Tag<'slice, 'seg>
+where
+    R: RangeBounds<usize>,
+{
+    fn slice(&self, range: R) -> Self {
+        Self(
+            &self.0[(range.start_bound().cloned(), range.end_bound().cloned())],
+        )
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg> InputIter for Tag<'slice, 'seg> {
+    type Item = &'seg str;
+    type Iter = Enumerate<Self::IterElem>;
+    type IterElem = SegmentContents<'slice, 'seg>;
+
+    fn iter_indices(&self) -> Self::Iter {
+        self.iter_elements().enumerate()
+    }
+
+    fn iter_elements(&self) -> Self::IterElem {
+        self.segments()
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.iter_elements().position(predicate)
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, nom::Needed> {
+        if self.input_len() >= count {
+            Ok(count)
+        } else {
+            Err(nom::Needed::new(count - self.input_len()))
+        }
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg> nom::InputTake for Tag<'slice, 'seg> {
+    fn take(&self, count: usize) -> Self {
+        self.slice(count ..)
+    }
+
+    fn take_split(&self, count: usize) -> (Self, Self) {
+        (self.slice(count ..), self.slice(.. count))
+    }
+}
+
+#[cfg(feature = "parse")]
+impl<'slice, 'seg> InputTakeAtPosition for Tag<'slice, 'seg> {
+    type Item = &'seg str;
+
+    fn split_at_position<P, E>(
+        &self,
+        predicate: P,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(pos) => Ok(self.take_split(pos)),
+            None => Err(nom::Err::Incomplete(nom::Needed::new(1))),
+        }
+    }
+
+    fn split_at_position1<P, E>(
+        &self,
+        predicate: P,
+        e: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(0) => Err(nom::Err::Error(E::from_error_kind(*self, e))),
+            Some(pos) => Ok(self.take_split(pos)),
+            None => Err(nom::Err::Incomplete(nom::Needed::new(1))),
+        }
+    }
+
+    fn split_at_position_complete<P, E>(
+        &self,
+        predicate: P,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(pos) => Ok(self.take_split(pos)),
+            None => Ok(self.take_split(self.input_len())),
+        }
+    }
+
+    fn split_at_position1_complete<P, E>(
+        &self,
+        predicate: P,
+        e: nom::error::ErrorKind,
+    ) -> nom::IResult<Self, Self, E>
+    where
+        P: Fn(Self::Item) -> bool,
+        E: nom::error::ParseError<Self>,
+    {
+        match self.position(predicate) {
+            Some(0) => Err(nom::Err::Error(E::from_error_kind(*self, e))),
+            Some(pos) => Ok(self.take_split(pos)),
+            None => {
+                if self.input_len() > 0 {
+                    Ok(self.take_split(self.input_len()))
+                } else {
+                    Err(nom::Err::Error(E::from_error_kind(*self, e)))
+                }
+            },
+        }
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl<'slice, 'seg> nom8::Input for Tag<'slice, 'seg> {
+    type Item = &'seg str;
+    type Iter = SegmentContents<'slice, 'seg>;
+    type IterIndices = Enumerate<SegmentContents<'slice, 'seg>>;
+
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    fn take(&self, index: usize) -> Self {
+        Self(&self.0[.. index])
+    }
+
+    fn take_from(&self, index: usize) -> Self {
+        Self(&self.0[index ..])
+    }
+
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        let (prefix, suffix) = self.0.split_at(index);
+        (Self(suffix), Self(prefix))
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.segments().position(predicate)
+    }
+
+    fn iter_elements(&self) -> Self::Iter {
+        self.segments()
+    }
+
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.segments().enumerate()
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, nom8::Needed> {
+        if self.len() >= count {
+            Ok(count)
+        } else {
+            Err(nom8::Needed::new(count - self.len()))
+        }
+    }
+}
+
+/// Implements the traits nom 7 and nom 8 share (offset, comparison, token and
+/// substring search, parsing and extension) for [`Tag`], and comparison and
+/// search of tags for spans, given the nom crate.
+macro_rules! impl_tag_comparison {
+    ($nom:ident) => {
+        const _: () = {
+            use $nom::{
+                Compare,
+                ExtendInto,
+                FindSubstring,
+                FindToken,
+                Offset,
+                ParseTo,
+            };
+
+            impl<'slice, 'seg> Offset for Tag<'slice, 'seg> {
+                fn offset(&self, second: &Self) -> usize {
+                    let this = self.0.as_ptr();
+                    let other = second.0.as_ptr();
+                    (other as usize - this as usize) / mem::size_of::<&str>()
+                }
+            }
+
+            impl<'slice0, 'seg0, 'slice1, 'seg1> Compare<Tag<'slice1, 'seg1>>
+                for Tag<'slice0, 'seg0>
+            {
+                fn compare(
+                    &self,
+                    input: Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    let mut this_iter = self.segments();
+                    let mut input_iter = input.segments();
+
+                    loop {
+                        match (this_iter.next(), input_iter.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if this_segment != input_segment {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    let folding = CaseFolding::default();
+                    let mut this_iter = self.segments();
+                    let mut input_iter = input.segments();
+
+                    loop {
+                        match (this_iter.next(), input_iter.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if !folding
+                                    .eq_ignore_case(this_segment, input_segment)
+                                {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+            }
+
+            impl<'slice0, 'seg0, 'slice1, 'seg1, 'tag>
+                Compare<&'tag Tag<'slice1, 'seg1>> for Tag<'slice0, 'seg0>
+            {
+                fn compare(
+                    &self,
+                    input: &'tag Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    self.compare(*input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(*input)
+                }
+            }
+
+            impl<'slice0, 'seg0, 'slice1, 'seg1, 'tag>
+                Compare<Tag<'slice1, 'seg1>> for &'tag Tag<'slice0, 'seg0>
+            {
+                fn compare(
+                    &self,
+                    input: Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    (**self).compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(input)
+                }
+            }
+
+            impl<'slice0, 'seg0, 'slice1, 'seg1, 'tag0, 'tag1>
+                Compare<&'tag1 Tag<'slice1, 'seg1>>
+                for &'tag0 Tag<'slice0, 'seg0>
+            {
+                fn compare(
+                    &self,
+                    input: &'tag1 Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    (**self).compare(*input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag1 Tag<'slice1, 'seg1>,
+                ) -> $nom::CompareResult {
+                    (**self).compare_no_case(*input)
+                }
+            }
+
+            impl<'slice, 'seg, 'span> Compare<&'span Span>
+                for Tag<'slice, 'seg>
+            {
+                fn compare(&self, input: &'span Span) -> $nom::CompareResult {
+                    let mut this_iter = self.segments();
+                    let mut input_iter = input.segments();
+
+                    loop {
+                        match (this_iter.next(), input_iter.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if this_segment != input_segment.as_str() {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'span Span,
+                ) -> $nom::CompareResult {
+                    let folding = input.source().case_folding();
+                    let mut this_iter = self.segments();
+                    let mut input_iter = input.segments();
+
+                    loop {
+                        match (this_iter.next(), input_iter.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if !folding.eq_ignore_case(
+                                    this_segment, &input_segment,
+                                ) {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+            }
+
+            impl<'slice, 'seg, 'span> Compare<Span> for Tag<'slice, 'seg> {
+                fn compare(&self, input: Span) -> $nom::CompareResult {
+                    self.compare(&input)
+                }
+
+                fn compare_no_case(&self, input: Span) -> $nom::CompareResult {
+                    self.compare_no_case(&input)
+                }
+            }
+
+            impl<'slice, 'seg, 'span> Compare<&'span SpanContent>
+                for Tag<'slice, 'seg>
+            {
+                fn compare(
+                    &self,
+                    input: &'span SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare(input.span())
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'span SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(input.span())
+                }
+            }
+
+            impl<'slice, 'seg, 'span> Compare<SpanContent>
+                for Tag<'slice, 'seg>
+            {
+                fn compare(&self, input: SpanContent) -> $nom::CompareResult {
+                    self.compare(&input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: SpanContent,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(&input)
+                }
+            }
+
+            impl<'slice, 'seg, 'span> Compare<Tag<'slice, 'seg>>
+                for &'span Span
+            {
+                fn compare(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    let mut this_iter = self.segments();
+                    let mut input_iter = input.segments();
+
+                    loop {
+                        match (this_iter.next(), input_iter.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if this_segment.as_str() != input_segment {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    let folding = self.source().case_folding();
+                    let mut this_iter = self.segments();
+                    let mut input_iter = input.segments();
+
+                    loop {
+                        match (this_iter.next(), input_iter.next()) {
+                            (Some(this_segment), Some(input_segment)) => {
+                                if !folding.eq_ignore_case(
+                                    &this_segment, input_segment,
+                                ) {
+                                    break $nom::CompareResult::Error;
+                                }
+                            },
+                            (None, Some(_)) => {
+                                break $nom::CompareResult::Incomplete
+                            },
+                            (_, None) => break $nom::CompareResult::Ok,
+                        }
+                    }
+                }
+            }
+
+            impl<'slice, 'seg> Compare<Tag<'slice, 'seg>> for Span {
+                fn compare(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    (&self).compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    (&self).compare_no_case(input)
+                }
+            }
+
+            impl<'slice, 'seg, 'span> Compare<Tag<'slice, 'seg>>
+                for &'span SpanContent
+            {
+                fn compare(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare_no_case(input)
+                }
+            }
+
+            impl<'slice, 'seg> Compare<Tag<'slice, 'seg>> for SpanContent {
+                fn compare(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare_no_case(input)
+                }
+            }
+
+            impl<'slice, 'seg, 'tag, 'span> Compare<&'tag Tag<'slice, 'seg>>
+                for &'span Span
+            {
+                fn compare(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.compare(*input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.compare_no_case(*input)
+                }
+            }
+
+            impl<'slice, 'seg, 'tag> Compare<&'tag Tag<'slice, 'seg>> for Span {
+                fn compare(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    (&self).compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    (&self).compare_no_case(input)
+                }
+            }
+
+            impl<'slice, 'seg, 'tag, 'span> Compare<&'tag Tag<'slice, 'seg>>
+                for &'span SpanContent
+            {
+                fn compare(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare_no_case(input)
+                }
+            }
+
+            impl<'slice, 'seg, 'tag> Compare<&'tag Tag<'slice, 'seg>>
+                for SpanContent
+            {
+                fn compare(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag Tag<'slice, 'seg>,
+                ) -> $nom::CompareResult {
+                    self.span().compare_no_case(input)
+                }
+            }
+
+            impl<'slice, 'seg, 'tok> FindToken<&'tok str>
+                for Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: &'tok str) -> bool {
+                    self.0.contains(&token)
+                }
+            }
+
+            impl<'slice, 'seg, 'tag, 'tok> FindToken<&'tok str>
+                for &'tag Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: &'tok str) -> bool {
+                    (**self).find_token(token)
+                }
+            }
+
+            impl<'slice, 'seg, 'tok, 'tok_ref> FindToken<&'tok_ref &'tok str>
+                for Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
+                    self.find_token(*token)
+                }
+            }
+
+            impl<'slice, 'seg, 'tag, 'tok, 'tok_ref>
+                FindToken<&'tok_ref &'tok str> for &'tag Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: &'tok_ref &'tok str) -> bool {
+                    (**self).find_token(token)
+                }
+            }
+
+            impl<'slice, 'seg, 'tok> FindToken<&'tok LocatedSegment>
+                for Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: &'tok LocatedSegment) -> bool {
+                    self.find_token(token.as_str())
+                }
+            }
+
+            impl<'slice, 'seg, 'tag, 'tok> FindToken<&'tok LocatedSegment>
+                for &'tag Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: &'tok LocatedSegment) -> bool {
+                    (**self).find_token(token)
+                }
+            }
+
+            impl<'slice, 'seg, 'tok> FindToken<LocatedSegment>
+                for Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    self.find_token(token.as_str())
+                }
+            }
+
+            impl<'slice, 'seg, 'tag, 'tok> FindToken<LocatedSegment>
+                for &'tag Tag<'slice, 'seg>
+            {
+                fn find_token(&self, token: LocatedSegment) -> bool {
+                    (**self).find_token(token)
+                }
+            }
+
+            /// A tag has no source whose segmenter could split the substring,
+            /// so it is split into extended grapheme clusters, the default
+            /// segmentation.
+            impl<'slice, 'seg, 'substr> FindSubstring<&'substr str>
+                for Tag<'slice, 'seg>
+            {
+                fn find_substring(
+                    &self,
+                    substr: &'substr str,
+                ) -> Option<usize> {
+                    find_segments(
+                        self.segments(),
+                        Segmentation::default()
+                            .segment_indices(substr)
+                            .map(|(_, segment)| segment),
+                        |segment, substr_segment| segment == substr_segment,
+                    )
+                }
+            }
+
+            impl<'slice0, 'seg0, 'slice1, 'seg1>
+                FindSubstring<Tag<'slice1, 'seg1>> for Tag<'slice0, 'seg0>
+            {
+                fn find_substring(
+                    &self,
+                    substr: Tag<'slice1, 'seg1>,
+                ) -> Option<usize> {
+                    find_segments(
+                        self.segments(),
+                        substr.segments(),
+                        |segment, substr_segment| segment == substr_segment,
+                    )
+                }
+            }
+
+            impl<'slice, 'seg> FindSubstring<Tag<'slice, 'seg>> for Span {
+                fn find_substring(
+                    &self,
+                    substr: Tag<'slice, 'seg>,
+                ) -> Option<usize> {
+                    find_segments(
+                        self.segments(),
+                        substr.segments(),
+                        |segment, substr_segment| {
+                            segment.as_str() == *substr_segment
+                        },
+                    )
+                }
+            }
+
+            impl<'slice, 'seg> FindSubstring<Tag<'slice, 'seg>>
+                for SpanContent
+            {
+                fn find_substring(
+                    &self,
+                    substr: Tag<'slice, 'seg>,
+                ) -> Option<usize> {
+                    self.span().find_substring(substr)
+                }
+            }
+
+            impl<'slice, 'seg, R> ParseTo<R> for Tag<'slice, 'seg>
+            where
+                R: FromStr,
+            {
+                fn parse_to(&self) -> Option<R> {
+                    self.0.concat().parse().ok()
+                }
+            }
+
+            impl<'slice, 'seg> ExtendInto for Tag<'slice, 'seg> {
+                type Item = char;
+                type Extender = String;
+
+                fn new_builder(&self) -> Self::Extender {
+                    String::new()
+                }
+
+                fn extend_into(&self, acc: &mut Self::Extender) {
+                    acc.extend(self.segments());
+                }
+            }
+        };
+    };
+}
+
+#[cfg(feature = "parse")]
+impl_tag_comparison!(nom);
+#[cfg(feature = "nom8")]
+impl_tag_comparison!(nom8);
+
+/// Iterator over segment contents of a [`Tag`]. See [`Tag::segments`].
+#[derive(Debug, Clone)]
+pub struct SegmentContents<'slice, 'seg> {
+    inner: slice::Iter<'slice, &'seg str>,
+}
+
+impl<'slice, 'seg> Iterator for SegmentContents<'slice, 'seg> {
+    type Item = &'seg str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().copied()
+    }
+}
+
+/// A tag that owns its segments, created by segmenting a string once. Unlike
+/// [`Tag`], it does not require splitting the string into grapheme clusters by
+/// hand. Tags are compared by their contents.
+#[derive(Debug, Clone)]
+pub struct OwnedTag(Span);
+
+impl OwnedTag {
+    /// Creates a tag by segmenting the given string into extended grapheme
+    /// clusters, with the default options of [`Source::new`]. Since tags are
+    /// compared segment by segment, such a tag only matches sources
+    /// segmented into extended grapheme clusters; use [`OwnedTag::new_with`]
+    /// for other sources.
+    pub fn new<S>(contents: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self(Source::new("tag", contents).full_span())
+    }
+
+    /// Creates a tag by segmenting the given string with the options of the
+    /// given builder, which should be the ones of the sources the tag is
+    /// matched against, e.g. the same [`Segmenter`].
+    pub fn new_with<S>(builder: &SourceBuilder, contents: S) -> Self
+    where
+        S: Into<Box<str>>,
+    {
+        Self(builder.finish("tag", contents).full_span())
+    }
+
+    /// Returns the length of the tag in segments/grapheme clusters.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Tests whether the tag contains no segments.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the contents of the tag as a string.
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+
+    /// Returns the tag as a span of the hidden source holding its contents.
+    pub fn as_span(&self) -> &Span {
+        &self.0
+    }
+
+    /// Returns an iterator over the located segments of this tag.
+    pub fn segments(&self) -> Segments {
+        self.0.segments()
+    }
+}
+
+impl IntoIterator for &OwnedTag {
+    type Item = LocatedSegment;
+    type IntoIter = Segments;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.segments()
+    }
+}
+
+impl FromStr for OwnedTag {
+    type Err = Infallible;
+
+    fn from_str(contents: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(contents))
+    }
+}
+
+impl<'contents> From<&'contents str> for OwnedTag {
+    fn from(contents: &'contents str) -> Self {
+        Self::new(contents)
+    }
+}
+
+impl From<String> for OwnedTag {
+    fn from(contents: String) -> Self {
+        Self::new(contents)
+    }
+}
+
+impl fmt::Display for OwnedTag {
+    fn fmt(&self, fmtr: &mut fmt::Formatter) -> fmt::Result {
+        fmtr.write_str(self.as_str())
+    }
+}
+
+impl PartialEq for OwnedTag {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for OwnedTag {}
+
+impl Hash for OwnedTag {
+    fn hash<H>(&self, state: &mut H)
+    where
+        H: Hasher,
+    {
+        self.as_str().hash(state)
+    }
+}
+
+#[cfg(feature = "parse")]
+impl InputLength for OwnedTag {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "parse")]
+impl InputLength for &OwnedTag {
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+}
+
+#[cfg(feature = "nom8")]
+impl nom8::Input for OwnedTag {
+    type Item = LocatedSegment;
+    type Iter = Segments;
+    type IterIndices = Enumerate<Segments>;
+
+    fn input_len(&self) -> usize {
+        self.len()
+    }
+
+    fn take(&self, index: usize) -> Self {
+        Self(nom8::Input::take(&self.0, index))
+    }
+
+    fn take_from(&self, index: usize) -> Self {
+        Self(nom8::Input::take_from(&self.0, index))
+    }
+
+    fn take_split(&self, index: usize) -> (Self, Self) {
+        let (suffix, prefix) = nom8::Input::take_split(&self.0, index);
+        (Self(suffix), Self(prefix))
+    }
+
+    fn position<P>(&self, predicate: P) -> Option<usize>
+    where
+        P: Fn(Self::Item) -> bool,
+    {
+        self.segments().position(predicate)
+    }
+
+    fn iter_elements(&self) -> Self::Iter {
+        self.segments()
+    }
+
+    fn iter_indices(&self) -> Self::IterIndices {
+        self.segments().enumerate()
+    }
+
+    fn slice_index(&self, count: usize) -> Result<usize, nom8::Needed> {
+        nom8::Input::slice_index(&self.0, count)
+    }
+}
+
+/// Implements comparison and substring search of owned tags with spans, given
+/// the nom crate. Comparisons are delegated to the span of the tag.
+macro_rules! impl_owned_tag_comparison {
+    ($nom:ident) => {
+        const _: () = {
+            use $nom::{Compare, CompareResult, FindSubstring};
+
+            impl<'input> Compare<&'input Span> for OwnedTag {
+                fn compare(&self, input: &'input Span) -> CompareResult {
+                    self.0.compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'input Span,
+                ) -> CompareResult {
+                    self.0.compare_no_case(input)
+                }
+            }
+
+            impl Compare<Span> for OwnedTag {
+                fn compare(&self, input: Span) -> CompareResult {
+                    self.0.compare(&input)
+                }
+
+                fn compare_no_case(&self, input: Span) -> CompareResult {
+                    self.0.compare_no_case(&input)
+                }
+            }
+
+            impl<'input> Compare<&'input SpanContent> for OwnedTag {
+                fn compare(&self, input: &'input SpanContent) -> CompareResult {
+                    self.0.compare(input)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'input SpanContent,
+                ) -> CompareResult {
+                    self.0.compare_no_case(input)
+                }
+            }
+
+            impl Compare<SpanContent> for OwnedTag {
+                fn compare(&self, input: SpanContent) -> CompareResult {
+                    self.0.compare(&input)
+                }
+
+                fn compare_no_case(&self, input: SpanContent) -> CompareResult {
+                    self.0.compare_no_case(&input)
+                }
+            }
+
+            impl<'input> Compare<&'input OwnedTag> for OwnedTag {
+                fn compare(&self, input: &'input OwnedTag) -> CompareResult {
+                    self.0.compare(&input.0)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'input OwnedTag,
+                ) -> CompareResult {
+                    self.0.compare_no_case(&input.0)
+                }
+            }
+
+            impl Compare<OwnedTag> for OwnedTag {
+                fn compare(&self, input: OwnedTag) -> CompareResult {
+                    self.0.compare(&input.0)
+                }
+
+                fn compare_no_case(&self, input: OwnedTag) -> CompareResult {
+                    self.0.compare_no_case(&input.0)
+                }
+            }
+
+            impl<'tag> Compare<&'tag OwnedTag> for Span {
+                fn compare(&self, input: &'tag OwnedTag) -> CompareResult {
+                    self.compare(&input.0)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag OwnedTag,
+                ) -> CompareResult {
+                    self.compare_no_case(&input.0)
+                }
+            }
+
+            impl Compare<OwnedTag> for Span {
+                fn compare(&self, input: OwnedTag) -> CompareResult {
+                    self.compare(&input.0)
+                }
+
+                fn compare_no_case(&self, input: OwnedTag) -> CompareResult {
+                    self.compare_no_case(&input.0)
+                }
+            }
+
+            impl<'span, 'tag> Compare<&'tag OwnedTag> for &'span Span {
+                fn compare(&self, input: &'tag OwnedTag) -> CompareResult {
+                    (**self).compare(&input.0)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag OwnedTag,
+                ) -> CompareResult {
+                    (**self).compare_no_case(&input.0)
+                }
+            }
+
+            impl<'span> Compare<OwnedTag> for &'span Span {
+                fn compare(&self, input: OwnedTag) -> CompareResult {
+                    (**self).compare(&input.0)
+                }
+
+                fn compare_no_case(&self, input: OwnedTag) -> CompareResult {
+                    (**self).compare_no_case(&input.0)
+                }
+            }
+
+            impl<'tag> Compare<&'tag OwnedTag> for SpanContent {
+                fn compare(&self, input: &'tag OwnedTag) -> CompareResult {
+                    self.compare(&input.0)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag OwnedTag,
+                ) -> CompareResult {
+                    self.compare_no_case(&input.0)
+                }
+            }
+
+            impl Compare<OwnedTag> for SpanContent {
+                fn compare(&self, input: OwnedTag) -> CompareResult {
+                    self.compare(&input.0)
+                }
+
+                fn compare_no_case(&self, input: OwnedTag) -> CompareResult {
+                    self.compare_no_case(&input.0)
+                }
+            }
+
+            impl<'span, 'tag> Compare<&'tag OwnedTag> for &'span SpanContent {
+                fn compare(&self, input: &'tag OwnedTag) -> CompareResult {
+                    (**self).compare(&input.0)
+                }
+
+                fn compare_no_case(
+                    &self,
+                    input: &'tag OwnedTag,
+                ) -> CompareResult {
+                    (**self).compare_no_case(&input.0)
+                }
+            }
+
+            impl<'span> Compare<OwnedTag> for &'span SpanContent {
+                fn compare(&self, input: OwnedTag) -> CompareResult {
+                    (**self).compare(&input.0)
+                }
+
+                fn compare_no_case(&self, input: OwnedTag) -> CompareResult {
+                    (**self).compare_no_case(&input.0)
+                }
+            }
+
+            impl<'tag> FindSubstring<&'tag OwnedTag> for Span {
+                fn find_substring(
+                    &self,
+                    substr: &'tag OwnedTag,
+                ) -> Option<usize> {
+                    self.find_substring(&substr.0)
+                }
+            }
+
+            impl FindSubstring<OwnedTag> for Span {
+                fn find_substring(&self, substr: OwnedTag) -> Option<usize> {
+                    self.find_substring(&substr.0)
+                }
+            }
+
+            impl<'tag> FindSubstring<&'tag OwnedTag> for SpanContent {
+                fn find_substring(
+                    &self,
+                    substr: &'tag OwnedTag,
+                ) -> Option<usize> {
+                    self.find_substring(&substr.0)
+                }
+            }
+
+            impl FindSubstring<OwnedTag> for SpanContent {
+                fn find_substring(&self, substr: OwnedTag) -> Option<usize> {
+                    self.find_substring(&substr.0)
+                }
+            }
+        };
+    };
+}
+
+#[cfg(feature = "parse")]
+impl_owned_tag_comparison!(nom);
+#[cfg(feature = "nom8")]
+impl_owned_tag_comparison!(nom8);